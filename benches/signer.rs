@@ -0,0 +1,137 @@
+//! Signer benchmarks
+//!
+//! Run with `cargo bench`
+
+use std::{
+    borrow::Cow,
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::UniformRand;
+use mina_signer::{
+    ct, CurvePoint, Hashable, Keypair, NetworkId, ROInput, ScalarField, Schnorr, SecKey, Signable,
+    Signer,
+};
+use oracle::{
+    pasta,
+    poseidon::{ArithmeticSponge, PlonkSpongeConstantsBasic, Sponge},
+};
+use rand::rngs::OsRng;
+
+const ITERATIONS: u32 = 1000;
+
+#[derive(Clone, Copy)]
+struct Message(u64);
+
+impl Hashable for Message {
+    fn to_roinput(self) -> ROInput {
+        let mut roi = ROInput::new();
+        roi.append_u64(self.0);
+
+        roi
+    }
+}
+
+impl Signable for Message {
+    fn domain_string(network_id: &NetworkId) -> Cow<'static, str> {
+        match network_id {
+            NetworkId::MAINNET => "MessageSigMainnet".into(),
+            NetworkId::TESTNET => "MessageSigTestnet".into(),
+            NetworkId::Custom(name) => format!("MessageSig{}", name).into(),
+        }
+    }
+}
+
+// Run `f` ITERATIONS times and print the average time per iteration
+fn bench<T, F: FnMut(u32) -> T>(name: &str, mut f: F) -> Duration {
+    black_box(f(0)); // Warm up (e.g. build lazily precomputed tables)
+
+    let start = Instant::now();
+    for i in 0..ITERATIONS {
+        black_box(f(i));
+    }
+    let avg = start.elapsed() / ITERATIONS;
+    println!("{:<32} {:>10.1?}", name, avg);
+
+    avg
+}
+
+fn main() {
+    let scalars: Vec<ScalarField> = (0..ITERATIONS)
+        .map(|_| ScalarField::rand(&mut OsRng))
+        .collect();
+
+    let generic = bench("generator mul (arkworks)", |i| {
+        CurvePoint::prime_subgroup_generator()
+            .mul(scalars[i as usize])
+            .into_affine()
+    });
+    bench("generator mul (ladder)", |i| {
+        ct::mul_generator_ladder(&scalars[i as usize])
+    });
+    let fixed_base = bench("generator mul (fixed-base table)", |i| {
+        ct::mul_generator(&scalars[i as usize])
+    });
+    println!(
+        "{:<32} {:>9.1}x",
+        "fixed-base speedup",
+        generic.as_secs_f64() / fixed_base.as_secs_f64()
+    );
+
+    bench("keypair from secret", |i| {
+        Keypair::from_secret(SecKey::new(scalars[i as usize])).expect("failed to create keypair")
+    });
+
+    let kp = Keypair::rand(&mut OsRng);
+    let mut ctx = mina_signer::create(NetworkId::TESTNET);
+    let sigs: Vec<_> = (0..ITERATIONS)
+        .map(|i| ctx.sign(&kp, Message(i.into())).expect("failed to sign"))
+        .collect();
+
+    bench("sign", |i| {
+        ctx.sign(&kp, Message(i.into())).expect("failed to sign")
+    });
+
+    // Average time per signature when signing ITERATIONS inputs at once
+    #[cfg(feature = "parallel")]
+    {
+        let msgs: Vec<Message> = (0..ITERATIONS).map(|i| Message(i.into())).collect();
+        black_box(ctx.sign_many(&kp, &msgs).expect("failed to sign"));
+        let start = Instant::now();
+        black_box(ctx.sign_many(&kp, &msgs).expect("failed to sign"));
+        println!(
+            "{:<32} {:>10.1?}",
+            "sign many",
+            start.elapsed() / ITERATIONS
+        );
+    }
+
+    bench("verify", |i| {
+        assert!(ctx.verify(sigs[i as usize], kp.public, Message(i.into())))
+    });
+
+    // Verification absorbing the domain prefix every time versus restoring the cached
+    // sponge state after the prefix
+    let mut uncached_ctx = Schnorr::<PlonkSpongeConstantsBasic>::new(
+        ArithmeticSponge::new(pasta::fp::params()),
+        NetworkId::TESTNET,
+    );
+    uncached_ctx.set_prefix_cache_capacity(0);
+    let uncached = bench("verify (no prefix cache)", |i| {
+        assert!(uncached_ctx.verify(sigs[i as usize], kp.public, Message(i.into())))
+    });
+    let mut cached_ctx = Schnorr::<PlonkSpongeConstantsBasic>::new(
+        ArithmeticSponge::new(pasta::fp::params()),
+        NetworkId::TESTNET,
+    );
+    let cached = bench("verify (prefix cache)", |i| {
+        assert!(cached_ctx.verify(sigs[i as usize], kp.public, Message(i.into())))
+    });
+    println!(
+        "{:<32} {:>10.1?}",
+        "saving per verification",
+        uncached.saturating_sub(cached)
+    );
+}
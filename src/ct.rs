@@ -0,0 +1,302 @@
+//! Constant-time helpers
+//!
+//! Side-channel hardened operations used for the secret dependent parts of signing and key
+//! derivation.
+//!
+//! **Guarantees**
+//!
+//! The following operations do not branch on, nor access memory depending on, secret data
+//!
+//! * Multiplication of the generator by a secret scalar ([mul_generator]), used for the
+//!   signing nonce commitment in [Signer::sign](crate::Signer::sign) and for deriving
+//!   public keys in [Keypair::from_secret](crate::Keypair::from_secret) and
+//!   [Keypair::validate](crate::Keypair::validate) (and as the starting point of
+//!   vanity address search).  It sums one entry of a precomputed table of
+//!   multiples of the generator per 4-bit window of the scalar using complete addition
+//!   formulas, where each entry is selected by scanning the whole row of the table.
+//!   [mul_generator_ladder] computes the same product without precomputation, using a
+//!   Montgomery ladder over all bits of the scalar with constant-time swaps, and is kept
+//!   as the reference implementation that the table is tested against.
+//! * Conditional negation of a secret scalar ([conditional_negate]), used to normalize
+//!   the signing nonce.
+//! * Comparison of secret keys ([SecKey](crate::SecKey) and [Keypair](crate::Keypair)
+//!   equality).
+//!
+//! **Limitations**
+//!
+//! * Field arithmetic is provided by arkworks, whose Montgomery multiplication and
+//!   conversion out of Montgomery form end with a data dependent final subtraction.
+//! * [mul_generator] reveals whether the scalar is zero.
+//! * The generator table is built (in variable time, from public data only) on first use.
+//! * Verification and batch verification operate on public data and are variable-time
+//!   (apart from their use of [mul_generator]).
+
+use ark_ec::{models::SWModelParameters, AffineCurve, ProjectiveCurve};
+use ark_ff::{
+    fields::{Fp256, Fp256Parameters},
+    BigInteger, BigInteger256, Field, FpParameters, One, PrimeField, Zero,
+};
+use std::sync::OnceLock;
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+use zeroize::Zeroize;
+
+use mina_curves::pasta::pallas::PallasParameters;
+
+use crate::{BaseField, CurvePoint, ScalarField};
+
+/// Window size in bits of the fixed-base generator table
+const WINDOW_BITS: usize = 4;
+/// Number of entries per window of the fixed-base generator table
+const WINDOW_SIZE: usize = 1 << WINDOW_BITS;
+/// Number of windows covering all scalar bits
+const WINDOWS: usize = 256 / WINDOW_BITS;
+
+/// Multiply the generator by scalar `k` in constant time
+pub fn mul_generator(k: &ScalarField) -> CurvePoint {
+    let table = generator_table();
+    let mut limbs = k.into_repr().0;
+
+    // Sum the table entries selected by each window of the scalar
+    let mut acc = Point::identity();
+    for (i, multiples) in table.iter().enumerate() {
+        let offset = i * WINDOW_BITS;
+        let digit = ((limbs[offset / 64] >> (offset % 64)) as usize & (WINDOW_SIZE - 1)) as u8;
+
+        // Scan all entries so that the memory access pattern is independent of the digit
+        let mut entry = Point::identity();
+        for (j, multiple) in multiples.iter().enumerate() {
+            Point::conditional_assign(&mut entry, multiple, (j as u8).ct_eq(&digit));
+        }
+        acc = acc.add(&entry);
+    }
+    limbs.zeroize();
+
+    acc.into_affine()
+}
+
+/// Multiply the generator by scalar `k` in constant time without precomputation
+///
+/// This is slower than [mul_generator], but does not build (nor keep in memory) the
+/// generator table.
+pub fn mul_generator_ladder(k: &ScalarField) -> CurvePoint {
+    let mut bits = k.into_repr();
+
+    // Montgomery ladder maintaining r1 = r0 + G
+    let mut r0 = Point::identity();
+    let mut r1 = Point::from_affine(&CurvePoint::prime_subgroup_generator());
+    for i in (0..<ScalarField as PrimeField>::Params::MODULUS_BITS as usize).rev() {
+        let bit = Choice::from(bits.get_bit(i) as u8);
+        Point::conditional_swap(&mut r0, &mut r1, bit);
+        r1 = r0.add(&r1);
+        r0 = r0.add(&r0);
+        Point::conditional_swap(&mut r0, &mut r1, bit);
+    }
+    bits.0.zeroize();
+
+    r0.into_affine()
+}
+
+/// Negate scalar `k` in constant time if `choice` is set
+pub fn conditional_negate(k: &ScalarField, choice: Choice) -> ScalarField {
+    select(k, &-*k, choice)
+}
+
+// Lazily built fixed-base table of multiples of the generator, whose row `i` contains
+// `j*2^(WINDOW_BITS*i)*G` for all window digits `j`
+fn generator_table() -> &'static [[Point; WINDOW_SIZE]] {
+    static TABLE: OnceLock<Vec<[Point; WINDOW_SIZE]>> = OnceLock::new();
+
+    TABLE.get_or_init(|| {
+        let mut base = CurvePoint::prime_subgroup_generator().into_projective();
+        (0..WINDOWS)
+            .map(|_| {
+                let mut multiples = vec![base; WINDOW_SIZE - 1];
+                for j in 1..multiples.len() {
+                    multiples[j] = multiples[j - 1] + base;
+                }
+                base = multiples[WINDOW_SIZE - 2] + base;
+
+                let mut row = [Point::identity(); WINDOW_SIZE];
+                for (entry, multiple) in row[1..]
+                    .iter_mut()
+                    .zip(ProjectiveCurve::batch_normalization_into_affine(&multiples))
+                {
+                    *entry = Point::from_affine(&multiple);
+                }
+
+                row
+            })
+            .collect()
+    })
+}
+
+// Select `b` if `choice` is set and `a` otherwise in constant time
+fn select<P: Fp256Parameters>(a: &Fp256<P>, b: &Fp256<P>, choice: Choice) -> Fp256<P> {
+    let mut limbs = [0u64; 4];
+    for (i, limb) in limbs.iter_mut().enumerate() {
+        *limb = u64::conditional_select(&(a.0).0[i], &(b.0).0[i], choice);
+    }
+
+    Fp256::new(BigInteger256(limbs))
+}
+
+// Curve point in homogeneous projective coordinates
+#[derive(Clone, Copy)]
+struct Point {
+    x: BaseField,
+    y: BaseField,
+    z: BaseField,
+}
+
+impl Point {
+    fn identity() -> Self {
+        Point {
+            x: BaseField::zero(),
+            y: BaseField::one(),
+            z: BaseField::zero(),
+        }
+    }
+
+    fn from_affine(point: &CurvePoint) -> Self {
+        Point {
+            x: point.x,
+            y: point.y,
+            z: BaseField::one(),
+        }
+    }
+
+    fn conditional_assign(&mut self, other: &Point, choice: Choice) {
+        self.x = select(&self.x, &other.x, choice);
+        self.y = select(&self.y, &other.y, choice);
+        self.z = select(&self.z, &other.z, choice);
+    }
+
+    fn conditional_swap(a: &mut Point, b: &mut Point, choice: Choice) {
+        let (x, y, z) = (
+            select(&a.x, &b.x, choice),
+            select(&a.y, &b.y, choice),
+            select(&a.z, &b.z, choice),
+        );
+        b.x = select(&b.x, &a.x, choice);
+        b.y = select(&b.y, &a.y, choice);
+        b.z = select(&b.z, &a.z, choice);
+        *a = Point { x, y, z };
+    }
+
+    // Complete addition for short Weierstrass curves with a = 0
+    // Details: Renes, Costello and Batina, "Complete addition formulas for prime order
+    //          elliptic curves", algorithm 7 <https://eprint.iacr.org/2015/1060>
+    fn add(&self, other: &Point) -> Point {
+        let b3 = PallasParameters::COEFF_B + PallasParameters::COEFF_B + PallasParameters::COEFF_B;
+
+        let mut t0 = self.x * other.x;
+        let mut t1 = self.y * other.y;
+        let mut t2 = self.z * other.z;
+        let mut t3 = (self.x + self.y) * (other.x + other.y);
+        let mut t4 = t0 + t1;
+        t3 -= t4;
+        t4 = (self.y + self.z) * (other.y + other.z);
+        let mut x3 = t1 + t2;
+        t4 -= x3;
+        x3 = (self.x + self.z) * (other.x + other.z);
+        let mut y3 = t0 + t2;
+        y3 = x3 - y3;
+        x3 = t0 + t0;
+        t0 += x3;
+        t2 *= b3;
+        let mut z3 = t1 + t2;
+        t1 -= t2;
+        y3 *= b3;
+        x3 = t4 * y3;
+        t2 = t3 * t1;
+        x3 = t2 - x3;
+        y3 *= t0;
+        t1 *= z3;
+        y3 += t1;
+        t0 *= t3;
+        z3 *= t4;
+        z3 += t0;
+
+        Point {
+            x: x3,
+            y: y3,
+            z: z3,
+        }
+    }
+
+    fn into_affine(self) -> CurvePoint {
+        if self.z.is_zero() {
+            return CurvePoint::zero();
+        }
+
+        // Invert by exponentiation with the public exponent p - 2 (Fermat)
+        let mut exponent = <BaseField as PrimeField>::Params::MODULUS;
+        exponent.sub_noborrow(&BigInteger256::from(2));
+        let z_inv = self.z.pow(exponent);
+
+        CurvePoint::new(self.x * z_inv, self.y * z_inv, false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ec::ProjectiveCurve;
+    use ark_ff::UniformRand;
+    use rand::rngs::OsRng;
+
+    #[test]
+    fn mul_generator() {
+        let generator = CurvePoint::prime_subgroup_generator();
+
+        assert_eq!(
+            super::mul_generator(&ScalarField::zero()),
+            CurvePoint::zero()
+        );
+        assert_eq!(super::mul_generator(&ScalarField::one()), generator);
+        assert_eq!(super::mul_generator(&-ScalarField::one()), -generator);
+
+        for k in [2u64, 3, 42, u64::MAX].iter() {
+            let k = ScalarField::from(*k);
+            assert_eq!(super::mul_generator(&k), generator.mul(k).into_affine());
+        }
+
+        for _ in 0..10 {
+            let k = ScalarField::rand(&mut OsRng);
+            assert_eq!(super::mul_generator(&k), generator.mul(k).into_affine());
+        }
+    }
+
+    #[test]
+    fn mul_generator_ladder() {
+        let generator = CurvePoint::prime_subgroup_generator();
+
+        assert_eq!(
+            super::mul_generator_ladder(&ScalarField::zero()),
+            CurvePoint::zero()
+        );
+        assert_eq!(super::mul_generator_ladder(&ScalarField::one()), generator);
+        assert_eq!(
+            super::mul_generator_ladder(&-ScalarField::one()),
+            -generator
+        );
+
+        // Fixed-base table agrees with the ladder
+        for _ in 0..10 {
+            let k = ScalarField::rand(&mut OsRng);
+            assert_eq!(super::mul_generator(&k), super::mul_generator_ladder(&k));
+            assert_eq!(
+                super::mul_generator_ladder(&k),
+                generator.mul(k).into_affine()
+            );
+        }
+    }
+
+    #[test]
+    fn conditional_negate() {
+        let k = ScalarField::rand(&mut OsRng);
+
+        assert_eq!(super::conditional_negate(&k, Choice::from(0)), k);
+        assert_eq!(super::conditional_negate(&k, Choice::from(1)), -k);
+    }
+}
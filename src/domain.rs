@@ -4,6 +4,7 @@
 
 use ark_ec::AffineCurve;
 use ark_ff::PrimeField; // for into_repr()
+use num_bigint::BigUint;
 
 use mina_curves::pasta::pallas as Pallas;
 
@@ -16,30 +17,42 @@ pub type ScalarField = <CurvePoint as AffineCurve>::ScalarField;
 
 use ark_serialize::CanonicalSerialize;
 
+use crate::Error;
+
 /// Field element helpers
 pub trait FieldHelpers<F: PrimeField> {
     /// Deserialize from bytes
-    fn from_bytes(bytes: &[u8]) -> Result<F, &str>;
+    fn from_bytes(bytes: &[u8]) -> Result<F, Error>;
 
     /// Deserialize from hex
-    fn from_hex(hex: &str) -> Result<F, &str>;
+    fn from_hex(hex: &str) -> Result<F, Error>;
 
     /// Serialize to bytes
     fn to_bytes(self) -> Vec<u8>;
 
     /// Serialize to hex
     fn to_hex(self) -> String;
+
+    /// Deserialize from decimal string
+    fn from_decimal(decimal: &str) -> Result<F, Error>;
+
+    /// Serialize to decimal string
+    fn to_decimal(self) -> String;
 }
 
 impl<F: PrimeField> FieldHelpers<F> for F {
-    fn from_bytes(bytes: &[u8]) -> Result<F, &str> {
-        F::deserialize(&mut &*bytes).map_err(|_| "failed to deserialize field bytes")
+    fn from_bytes(bytes: &[u8]) -> Result<F, Error> {
+        if bytes.len() != F::zero().to_bytes().len() {
+            return Err(Error::Length);
+        }
+
+        F::deserialize(&mut &*bytes).map_err(|_| Error::Field)
     }
 
-    fn from_hex(hex: &str) -> Result<F, &str> {
-        let bytes: Vec<u8> = hex::decode(hex).map_err(|_| "Failed to decode field hex")?;
+    fn from_hex(hex: &str) -> Result<F, Error> {
+        let bytes: Vec<u8> = hex::decode(hex).map_err(|_| Error::Hex)?;
 
-        F::deserialize(&mut &bytes[..]).map_err(|_| "Failed to deserialize field bytes")
+        F::from_bytes(&bytes)
     }
 
     fn to_bytes(self) -> Vec<u8> {
@@ -54,6 +67,26 @@ impl<F: PrimeField> FieldHelpers<F> for F {
     fn to_hex(self) -> String {
         hex::encode(self.to_bytes())
     }
+
+    fn from_decimal(decimal: &str) -> Result<F, Error> {
+        if decimal.is_empty() || !decimal.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(Error::Decimal);
+        }
+
+        let mut bytes = BigUint::parse_bytes(decimal.as_bytes(), 10)
+            .ok_or(Error::Decimal)?
+            .to_bytes_le();
+        if bytes.len() > F::zero().to_bytes().len() {
+            return Err(Error::Field);
+        }
+        bytes.resize(F::zero().to_bytes().len(), 0);
+
+        F::deserialize(&mut &bytes[..]).map_err(|_| Error::Field)
+    }
+
+    fn to_decimal(self) -> String {
+        BigUint::from_bytes_le(&self.to_bytes()).to_string()
+    }
 }
 
 #[cfg(test)]
@@ -62,27 +95,25 @@ mod tests {
 
     #[test]
     fn field_from_hex() {
-        assert_eq!(
-            BaseField::from_hex(""),
-            Err("Failed to deserialize field bytes")
-        );
+        assert_eq!(BaseField::from_hex(""), Err(Error::Length));
+        assert_eq!(BaseField::from_hex(&"00".repeat(33)), Err(Error::Length));
         assert_eq!(
             BaseField::from_hex("1428fadcf0c02396e620f14f176fddb5d769b7de2027469d027a80142ef8f07"),
-            Err("Failed to decode field hex")
+            Err(Error::Hex)
         );
         assert_eq!(
             BaseField::from_hex(
                 "0f5314f176fddb5d769b7de2027469d027ad428fadcf0c02396e6280142efb7d8"
             ),
-            Err("Failed to decode field hex")
+            Err(Error::Hex)
         );
         assert_eq!(
             BaseField::from_hex("g64244176fddb5d769b7de2027469d027ad428fadcf0c02396e6280142efb7d8"),
-            Err("Failed to decode field hex")
+            Err(Error::Hex)
         );
         assert_eq!(
             BaseField::from_hex("0cdaf334e9632268a5aa959c2781fb32bf45565fe244ae42c849d3fdc7c644fd"),
-            Err("Failed to deserialize field bytes")
+            Err(Error::Field)
         );
 
         assert_eq!(
@@ -93,34 +124,74 @@ mod tests {
     }
 
     #[test]
-    fn scalar_from_hex() {
+    fn field_decimal() {
+        assert_eq!(BaseField::from(0u64).to_decimal(), "0");
+        assert_eq!(BaseField::from(1234567890u64).to_decimal(), "1234567890");
+        assert_eq!(
+            BaseField::from_decimal("1234567890"),
+            Ok(BaseField::from(1234567890u64))
+        );
+        assert_eq!(BaseField::from_decimal("00042"), Ok(BaseField::from(42u64)));
+
+        // Field modulus minus one is the largest canonical element
+        let max = "28948022309329048855892746252171976963363056481941560715954676764349967630336";
+        assert_eq!(BaseField::from_decimal(max), Ok(-BaseField::from(1u64)));
+        assert_eq!((-BaseField::from(1u64)).to_decimal(), max);
+        assert_eq!(
+            BaseField::from_decimal(
+                "28948022309329048855892746252171976963363056481941560715954676764349967630337"
+            ),
+            Err(Error::Field)
+        );
+        assert_eq!(BaseField::from_decimal(&"9".repeat(100)), Err(Error::Field));
+
+        assert_eq!(BaseField::from_decimal(""), Err(Error::Decimal));
+        assert_eq!(BaseField::from_decimal("-1"), Err(Error::Decimal));
+        assert_eq!(BaseField::from_decimal("+1"), Err(Error::Decimal));
+        assert_eq!(BaseField::from_decimal("1_000"), Err(Error::Decimal));
+        assert_eq!(BaseField::from_decimal("0x10"), Err(Error::Decimal));
+    }
+
+    #[test]
+    fn scalar_decimal() {
+        // Scalar modulus minus one is the largest canonical element
+        let max = "28948022309329048855892746252171976963363056481941647379679742748393362948096";
+        assert_eq!(ScalarField::from_decimal(max), Ok(-ScalarField::from(1u64)));
+        assert_eq!((-ScalarField::from(1u64)).to_decimal(), max);
         assert_eq!(
-            ScalarField::from_hex(""),
-            Err("Failed to deserialize field bytes")
+            ScalarField::from_decimal(
+                "28948022309329048855892746252171976963363056481941647379679742748393362948097"
+            ),
+            Err(Error::Field)
         );
+    }
+
+    #[test]
+    fn scalar_from_hex() {
+        assert_eq!(ScalarField::from_hex(""), Err(Error::Length));
         assert_eq!(
             ScalarField::from_hex(
                 "1428fadcf0c02396e620f14f176fddb5d769b7de2027469d027a80142ef8f07"
             ),
-            Err("Failed to decode field hex")
+            Err(Error::Hex)
         );
         assert_eq!(
             ScalarField::from_hex(
                 "0f5314f176fddb5d769b7de2027469d027ad428fadcf0c02396e6280142efb7d8"
             ),
-            Err("Failed to decode field hex")
+            Err(Error::Hex)
         );
         assert_eq!(
             ScalarField::from_hex(
                 "g64244176fddb5d769b7de2027469d027ad428fadcf0c02396e6280142efb7d8"
             ),
-            Err("Failed to decode field hex")
+            Err(Error::Hex)
         );
         assert_eq!(
             ScalarField::from_hex(
                 "817bfe2410826e69320c0ccdaf824da720d9647202ed7b967d5bddf6714424dd"
             ),
-            Err("Failed to deserialize field bytes")
+            Err(Error::Field)
         );
 
         assert_eq!(
@@ -0,0 +1,165 @@
+//! Mina signer error type
+//!
+//! Definition of the error returned by all fallible operations of the crate
+
+use std::fmt;
+
+/// Mina signer error
+#[derive(Clone, Copy, fmt::Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Invalid hex encoding
+    Hex,
+
+    /// Invalid base58 encoding
+    Base58,
+
+    /// Invalid decimal encoding
+    Decimal,
+
+    /// Invalid JSON encoding
+    Json,
+
+    /// Invalid length
+    Length,
+
+    /// Invalid base58 check checksum
+    Checksum,
+
+    /// Invalid version info
+    Version,
+
+    /// Invalid curve point at infinity
+    PointAtInfinity,
+
+    /// Invalid curve point not on the curve
+    PointNotOnCurve,
+
+    /// Invalid x-coordinate, i.e. there is no curve point with this x-coordinate
+    XCoordinate,
+
+    /// Invalid y-coordinate parity
+    Parity,
+
+    /// Invalid (i.e. non-canonical) base or scalar field element
+    Field,
+
+    /// Invalid (i.e. non-canonical) signature field component
+    SignatureField,
+
+    /// Invalid (i.e. non-canonical) signature scalar component
+    SignatureScalar,
+
+    /// Invalid signature (see [crate::VerifyError])
+    Signature,
+
+    /// Invalid public key (see [crate::PubKey::validate])
+    PublicKey,
+
+    /// Invalid secret key scalar
+    SecretKey,
+
+    /// Keypair public key does not match secret key
+    KeypairMismatch,
+
+    /// Invalid domain string (i.e. longer than 20 characters or not ASCII)
+    Domain,
+
+    /// Invalid network id (e.g. empty or non-ASCII network name)
+    NetworkId,
+
+    /// Invalid HD account index
+    AccountIndex,
+
+    /// Invalid HD seed
+    Seed,
+
+    /// Invalid mnemonic word count
+    MnemonicWordCount,
+
+    /// Invalid mnemonic word
+    MnemonicWord,
+
+    /// Invalid mnemonic checksum
+    MnemonicChecksum,
+
+    /// Invalid mnemonic
+    Mnemonic,
+
+    /// Unsupported secret box primitive
+    Primitive,
+
+    /// Invalid secret box password hashing difficulty
+    PasswordDifficulty,
+
+    /// Secret box password hashing failed
+    PasswordHash,
+
+    /// Secret box encryption failed
+    Encryption,
+
+    /// Secret box decryption failed (e.g. wrong password)
+    Decryption,
+
+    /// Impossible vanity address prefix
+    VanityPrefix,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            Error::Hex => "Invalid hex encoding",
+            Error::Base58 => "Invalid base58 encoding",
+            Error::Decimal => "Invalid decimal encoding",
+            Error::Json => "Invalid JSON encoding",
+            Error::Length => "Invalid length",
+            Error::Checksum => "Invalid checksum",
+            Error::Version => "Invalid version info",
+            Error::PointAtInfinity => "Invalid curve point at infinity",
+            Error::PointNotOnCurve => "Invalid curve point not on curve",
+            Error::XCoordinate => "Invalid x-coordinate",
+            Error::Parity => "Invalid y-coordinate parity",
+            Error::Field => "Invalid field element",
+            Error::SignatureField => "Invalid signature field component",
+            Error::SignatureScalar => "Invalid signature scalar component",
+            Error::Signature => "Invalid signature",
+            Error::PublicKey => "Invalid public key",
+            Error::SecretKey => "Invalid secret key scalar",
+            Error::KeypairMismatch => "Keypair public key does not match secret key",
+            Error::Domain => "Invalid domain string",
+            Error::NetworkId => "Invalid network id",
+            Error::AccountIndex => "Invalid account index",
+            Error::Seed => "Invalid seed",
+            Error::MnemonicWordCount => "Invalid mnemonic word count",
+            Error::MnemonicWord => "Invalid mnemonic word",
+            Error::MnemonicChecksum => "Invalid mnemonic checksum",
+            Error::Mnemonic => "Invalid mnemonic",
+            Error::Primitive => "Unsupported secret box primitive",
+            Error::PasswordDifficulty => "Invalid secret box password difficulty",
+            Error::PasswordHash => "Failed to hash secret box password",
+            Error::Encryption => "Failed to encrypt secret box",
+            Error::Decryption => "Failed to decrypt secret box (wrong password?)",
+            Error::VanityPrefix => "Impossible vanity address prefix",
+        };
+
+        write!(f, "{}", msg)
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display() {
+        assert_eq!(Error::Checksum.to_string(), "Invalid checksum");
+        assert_eq!(
+            Error::Decryption.to_string(),
+            "Failed to decrypt secret box (wrong password?)"
+        );
+
+        let err: Box<dyn std::error::Error> = Box::new(Error::Hex);
+        assert_eq!(err.to_string(), "Invalid hex encoding");
+    }
+}
@@ -0,0 +1,349 @@
+//! Mina Poseidon hashing
+//!
+//! Mina's Poseidon hash with domain prefixes (e.g. Mina's `Poseidon.hashWithPrefix`), using the
+//! legacy or kimchi parameters.  This is the hash used by the signer (see [crate::schnorr]),
+//! but it may also be used to compute other Mina hashes, such as memo hashes, account hashes
+//! and zkApp commitments.
+//!
+//! **Example**
+//!
+//! ```
+//! use mina_signer::{hash, BaseField, ROInput};
+//!
+//! let mut hasher = hash::create_kimchi();
+//! let h = hasher
+//!     .hash_with_prefix("MinaAcctUpdateNode", &[BaseField::from(1u64), BaseField::from(2u64)])
+//!     .expect("failed to hash");
+//!
+//! let mut roi = ROInput::new();
+//! roi.append_field(h);
+//! roi.append_u64(42);
+//! let h = hasher.hash(&roi);
+//! ```
+
+use ark_ff::Zero;
+use std::collections::HashMap;
+
+use oracle::{
+    pasta,
+    poseidon::{
+        ArithmeticSponge, PlonkSpongeConstants15W, PlonkSpongeConstantsBasic, Sponge,
+        SpongeConstants, SpongeState,
+    },
+};
+
+use crate::{BaseField, Error, FieldHelpers, ROInput};
+
+/// Maximum length of domain prefixes
+pub const MAX_PREFIX_LEN: usize = 20;
+
+/// Default maximum number of domain prefixes whose sponge state a hasher caches
+pub const DEFAULT_PREFIX_CACHE_CAPACITY: usize = 16;
+
+/// Poseidon hasher context
+///
+/// Legacy hashers hash the legacy serialization of random oracle inputs (see [ROInput::to_fields]),
+/// while kimchi hashers hash their packed serialization (see [ROInput::to_packed_fields]).
+///
+/// The sponge state after absorbing a domain prefix only depends on the prefix (which for
+/// signatures is determined by the input type and network), so hashers cache it for the first
+/// prefixes they are used with, up to their prefix cache capacity (see
+/// [Hasher::set_prefix_cache_capacity]).
+#[derive(Clone)]
+pub struct Hasher<SC: SpongeConstants> {
+    sponge: ArithmeticSponge<BaseField, SC>,
+    kimchi: bool,
+    prefixes: HashMap<String, (Vec<BaseField>, SpongeState)>,
+    prefix_cache_capacity: usize,
+}
+
+impl<SC: SpongeConstants> Hasher<SC> {
+    /// Create a new legacy hasher context using arithmetic sponge defined by `sponge`
+    pub fn new(sponge: ArithmeticSponge<BaseField, SC>) -> Self {
+        Hasher {
+            sponge,
+            kimchi: false,
+            prefixes: HashMap::new(),
+            prefix_cache_capacity: DEFAULT_PREFIX_CACHE_CAPACITY,
+        }
+    }
+
+    /// Create a new kimchi hasher context using arithmetic sponge defined by `sponge`
+    pub fn new_kimchi(sponge: ArithmeticSponge<BaseField, SC>) -> Self {
+        Hasher {
+            sponge,
+            kimchi: true,
+            prefixes: HashMap::new(),
+            prefix_cache_capacity: DEFAULT_PREFIX_CACHE_CAPACITY,
+        }
+    }
+
+    /// Check whether this is a kimchi hasher
+    pub fn is_kimchi(&self) -> bool {
+        self.kimchi
+    }
+
+    /// Set the maximum number of domain prefixes whose sponge state is cached (0 disables
+    /// caching), dropping the cached states if there are more
+    pub fn set_prefix_cache_capacity(&mut self, capacity: usize) {
+        self.prefix_cache_capacity = capacity;
+        if self.prefixes.len() > capacity {
+            self.prefixes.clear();
+        }
+    }
+
+    /// Hash field elements `input` with domain `prefix` (see [prefix_to_field])
+    pub fn hash_with_prefix(
+        &mut self,
+        prefix: &str,
+        input: &[BaseField],
+    ) -> Result<BaseField, Error> {
+        // N.B. Mina sets the sponge's initial state by hashing the domain prefix
+        match self.prefixes.get(prefix) {
+            Some((state, sponge_state)) => {
+                self.sponge.state = state.clone();
+                self.sponge.sponge_state = sponge_state.clone();
+            }
+            None => {
+                let prefix_field = prefix_to_field(prefix)?;
+                self.reset();
+                self.sponge.absorb(&[prefix_field]);
+                self.sponge.squeeze();
+
+                if self.prefixes.len() < self.prefix_cache_capacity {
+                    self.prefixes.insert(
+                        prefix.to_string(),
+                        (self.sponge.state.clone(), self.sponge.sponge_state.clone()),
+                    );
+                }
+            }
+        }
+
+        Ok(self.absorb_and_squeeze(input))
+    }
+
+    /// Hash random oracle `input` (without domain prefix)
+    pub fn hash(&mut self, input: &ROInput) -> BaseField {
+        self.reset();
+
+        self.absorb_and_squeeze(&self.to_fields(input))
+    }
+
+    /// Serialize random oracle `input` to field elements for hashing
+    pub fn to_fields(&self, input: &ROInput) -> Vec<BaseField> {
+        if self.kimchi {
+            input.to_packed_fields()
+        } else {
+            input.to_fields()
+        }
+    }
+
+    // Set sponge initial state (explicitly init state so hasher context can be reused)
+    fn reset(&mut self) {
+        self.sponge.state = vec![BaseField::zero(); self.sponge.state.len()];
+        self.sponge.sponge_state = SpongeState::Absorbed(0);
+    }
+
+    fn absorb_and_squeeze(&mut self, input: &[BaseField]) -> BaseField {
+        // Mina permutes the state once when hashing empty input, which is the same as
+        // absorbing a single zero
+        if input.is_empty() {
+            self.sponge.absorb(&[BaseField::zero()]);
+        } else {
+            self.sponge.absorb(input);
+        }
+
+        self.sponge.squeeze()
+    }
+}
+
+/// Create a legacy hasher context (i.e. as used by [crate::create])
+pub fn create_legacy() -> Hasher<PlonkSpongeConstantsBasic> {
+    Hasher::new(ArithmeticSponge::new(pasta::fp::params()))
+}
+
+/// Create a kimchi hasher context (i.e. as used by [crate::create_kimchi])
+pub fn create_kimchi() -> Hasher<PlonkSpongeConstants15W> {
+    Hasher::new_kimchi(ArithmeticSponge::new(pasta::fp_3::params()))
+}
+
+/// Convert domain `prefix` into a field element
+///
+/// Domain prefixes must be ASCII with length at most [MAX_PREFIX_LEN] and are padded with `*`.
+pub fn prefix_to_field(prefix: &str) -> Result<BaseField, Error> {
+    if prefix.len() > MAX_PREFIX_LEN || !prefix.is_ascii() {
+        return Err(Error::Domain);
+    }
+
+    let mut bytes = format!("{:*<width$}", prefix, width = MAX_PREFIX_LEN).into_bytes();
+    bytes.resize(32, 0);
+
+    BaseField::from_bytes(&bytes).map_err(|_| Error::Domain)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefix_to_field() {
+        assert_eq!(
+            super::prefix_to_field("CodaSignature").map(|f| f.to_bytes()),
+            Ok(b"CodaSignature*******\0\0\0\0\0\0\0\0\0\0\0\0".to_vec())
+        );
+        assert_eq!(
+            super::prefix_to_field("MinaSignatureMainnet").map(|f| f.to_bytes()),
+            Ok(b"MinaSignatureMainnet\0\0\0\0\0\0\0\0\0\0\0\0".to_vec())
+        );
+        assert_eq!(
+            super::prefix_to_field(""),
+            super::prefix_to_field("********************")
+        );
+        assert_eq!(
+            super::prefix_to_field("MinaSignatureMainnet1"),
+            Err(Error::Domain)
+        );
+        assert_eq!(
+            super::prefix_to_field("MinaSignatur\u{e9}"),
+            Err(Error::Domain)
+        );
+    }
+
+    #[test]
+    fn hash_with_prefix() {
+        check_hash_with_prefix(create_legacy());
+        check_hash_with_prefix(create_kimchi());
+    }
+
+    fn check_hash_with_prefix<SC: SpongeConstants>(mut hasher: Hasher<SC>) {
+        let one = BaseField::from(1u64);
+        let two = BaseField::from(2u64);
+
+        let h = hasher
+            .hash_with_prefix("MinaSignatureMainnet", &[one, two])
+            .expect("failed to hash");
+
+        // Hasher is reusable
+        assert_eq!(
+            hasher.hash_with_prefix("MinaSignatureMainnet", &[one, two]),
+            Ok(h)
+        );
+        assert_ne!(hasher.hash_with_prefix("CodaSignature", &[one, two]), Ok(h));
+        assert_ne!(
+            hasher.hash_with_prefix("MinaSignatureMainnet", &[two, one]),
+            Ok(h)
+        );
+        assert_ne!(
+            hasher.hash_with_prefix("MinaSignatureMainnet", &[one, two, one]),
+            Ok(h)
+        );
+
+        // Empty input is hashed like a single zero (as in Mina)
+        assert_eq!(
+            hasher.hash_with_prefix("CodaSignature", &[]),
+            hasher.hash_with_prefix("CodaSignature", &[BaseField::zero()])
+        );
+
+        assert_eq!(
+            hasher.hash_with_prefix("MinaSignatureMainnet*", &[one]),
+            Err(Error::Domain)
+        );
+    }
+
+    #[test]
+    fn prefix_cache() {
+        let one = BaseField::from(1u64);
+        let mut hasher = create_kimchi();
+
+        let h = hasher
+            .hash_with_prefix("MinaSignatureMainnet", &[one])
+            .expect("failed to hash");
+        let other = hasher
+            .hash_with_prefix("CodaSignature", &[one])
+            .expect("failed to hash");
+        assert_eq!(hasher.prefixes.len(), 2);
+
+        // Cached prefix states give the same hashes as fresh hashers
+        for _ in 0..3 {
+            assert_eq!(
+                hasher.hash_with_prefix("MinaSignatureMainnet", &[one]),
+                Ok(h)
+            );
+            assert_eq!(hasher.hash_with_prefix("CodaSignature", &[one]), Ok(other));
+            assert_eq!(
+                hasher.hash_with_prefix("CodaSignature", &[]),
+                create_kimchi().hash_with_prefix("CodaSignature", &[])
+            );
+        }
+        assert_eq!(hasher.prefixes.len(), 2);
+
+        // Hashing without prefix is unaffected
+        let roi = ROInput::new();
+        assert_eq!(hasher.hash(&roi), create_kimchi().hash(&roi));
+
+        // Invalid prefixes are not cached
+        assert_eq!(
+            hasher.hash_with_prefix("MinaSignatureMainnet*", &[one]),
+            Err(Error::Domain)
+        );
+        assert_eq!(hasher.prefixes.len(), 2);
+    }
+
+    #[test]
+    fn prefix_cache_capacity() {
+        let one = BaseField::from(1u64);
+        let prefixes: Vec<String> = (0..DEFAULT_PREFIX_CACHE_CAPACITY + 4)
+            .map(|i| format!("Prefix{}", i))
+            .collect();
+        let mut hasher = create_kimchi();
+
+        // Cache stops growing once full, but hashes are unaffected
+        for prefix in prefixes.iter() {
+            assert_eq!(
+                hasher.hash_with_prefix(prefix, &[one]),
+                create_kimchi().hash_with_prefix(prefix, &[one])
+            );
+        }
+        assert_eq!(hasher.prefixes.len(), DEFAULT_PREFIX_CACHE_CAPACITY);
+        for prefix in prefixes.iter() {
+            assert_eq!(
+                hasher.hash_with_prefix(prefix, &[one]),
+                create_kimchi().hash_with_prefix(prefix, &[one])
+            );
+        }
+        assert!(hasher.prefixes.contains_key("Prefix0"));
+        assert!(!hasher
+            .prefixes
+            .contains_key(prefixes[prefixes.len() - 1].as_str()));
+
+        // Shrinking the capacity drops the cached states and no capacity disables caching
+        hasher.set_prefix_cache_capacity(2);
+        assert!(hasher.prefixes.is_empty());
+        hasher.set_prefix_cache_capacity(0);
+        assert_eq!(
+            hasher.hash_with_prefix("Prefix0", &[one]),
+            create_kimchi().hash_with_prefix("Prefix0", &[one])
+        );
+        assert!(hasher.prefixes.is_empty());
+    }
+
+    #[test]
+    fn hash() {
+        let mut roi = ROInput::new();
+        roi.append_field(BaseField::from(31u64));
+        roi.append_u32(42);
+        roi.append_bit(true);
+
+        let mut legacy = create_legacy();
+        let mut kimchi = create_kimchi();
+        assert!(!legacy.is_kimchi());
+        assert!(kimchi.is_kimchi());
+        assert_eq!(legacy.to_fields(&roi), roi.to_fields());
+        assert_eq!(kimchi.to_fields(&roi), roi.to_packed_fields());
+
+        let h = legacy.hash(&roi);
+        assert_eq!(legacy.hash(&roi), h);
+        assert_ne!(kimchi.hash(&roi), h);
+        assert_eq!(legacy.hash(&ROInput::new()), legacy.hash(&ROInput::new()));
+    }
+}
@@ -0,0 +1,126 @@
+//! Hierarchical deterministic key derivation
+//!
+//! BIP32/BIP44 derivation of Mina keypairs from a seed.
+//!
+//! Keys are derived on secp256k1 along the path `m/44'/12586'/account'/0/0` and the
+//! resulting 32 bytes are reduced into a scalar field element by masking the top two
+//! bits.  This matches the derivation used by the Ledger Mina app and the Auro wallet.
+
+use bip32::{DerivationPath, XPrv};
+use zeroize::Zeroize;
+
+use crate::{Error, FieldHelpers, Keypair, ScalarField, SecKey};
+
+/// Mina BIP44 coin type
+pub const MINA_COIN_TYPE: u32 = 12586;
+
+/// Maximum account index (account indices are hardened)
+pub const MAX_ACCOUNT: u32 = (1 << 31) - 1;
+
+/// Obtain the BIP44 derivation path for Mina account `account`
+pub fn derivation_path(account: u32) -> String {
+    format!("m/44'/{}'/{}'/0/0", MINA_COIN_TYPE, account)
+}
+
+/// Derive the secret key scalar for Mina account `account` from BIP32 `seed`
+pub fn derive_secret(seed: &[u8], account: u32) -> Result<ScalarField, Error> {
+    if account > MAX_ACCOUNT {
+        return Err(Error::AccountIndex);
+    }
+
+    let path: DerivationPath = derivation_path(account)
+        .parse()
+        .map_err(|_| Error::AccountIndex)?;
+    let xprv = XPrv::derive_from_path(seed, &path).map_err(|_| Error::Seed)?;
+
+    let mut bytes = xprv.to_bytes();
+    // Drop the top two bits to convert into a scalar field element
+    //   N.B. The derived bytes are big-endian, whereas mina scalars are little-endian
+    bytes[0] &= 0b0011_1111;
+    bytes.reverse();
+
+    let secret = ScalarField::from_bytes(&bytes);
+    bytes.zeroize();
+
+    secret
+}
+
+/// Derive the keypair for Mina account `account` from BIP32 `seed`
+pub fn derive_keypair(seed: &[u8], account: u32) -> Result<Keypair, Error> {
+    Keypair::from_secret(SecKey::new(derive_secret(seed, account)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Seed of the Ledger Mina app test mnemonic "course grief vintage slim tell hospital car
+    // maze model style elegant kitchen state purpose matrix gas grid enable frown road goddess
+    // glove canyon key"
+    const TEST_SEED: &str = "1bac2a4281e6095c3945cdefef36261d1af600f7b1a0d7fd473f3acfb7c614326261589ee860375c5c977f2ba93bd67d03bd502fda50004c787588ec57d8f0fb";
+
+    #[test]
+    fn derivation_path() {
+        assert_eq!(super::derivation_path(0), "m/44'/12586'/0'/0/0");
+        assert_eq!(super::derivation_path(49370), "m/44'/12586'/49370'/0/0");
+    }
+
+    #[test]
+    fn derive_keypair() {
+        let seed = hex::decode(TEST_SEED).expect("invalid seed hex");
+
+        macro_rules! assert_derive_keypair_eq {
+            ($account:expr, $sec_key_hex:expr, $target_address:expr) => {
+                let kp = super::derive_keypair(&seed, $account).expect("failed to derive keypair");
+                assert_eq!(
+                    kp,
+                    Keypair::from_hex($sec_key_hex).expect("failed to create keypair")
+                );
+                assert_eq!(kp.get_address(), $target_address);
+            };
+        }
+
+        assert_derive_keypair_eq!(
+            0,
+            "164244176fddb5d769b7de2027469d027ad428fadcc0c02396e6280142efb718",
+            "B62qnzbXmRNo9q32n4SNu2mpB8e7FYYLH8NmaX6oFCBYjjQ8SbD7uzV"
+        );
+        assert_derive_keypair_eq!(
+            1,
+            "3ca187a58f09da346844964310c7e0dd948a9105702b716f4d732e042e0c172e",
+            "B62qicipYxyEHu7QjUqS7QvBipTs5CzgkYZZZkPoKVYBu6tnDUcE9Zt"
+        );
+        assert_derive_keypair_eq!(
+            2,
+            "336eb4a19b3d8905824b0f2254fb495573be302c17582748bf7e101965aa4774",
+            "B62qrKG4Z8hnzZqp1AL8WsQhQYah3quN1qUj3SyfJA8Lw135qWWg1mi"
+        );
+        assert_derive_keypair_eq!(
+            3,
+            "1dee867358d4000f1dafa5978341fb515f89eeddbe450bd57df091f1e63d4444",
+            "B62qoqiAgERjCjXhofXiD7cMLJSKD8hE8ZtMh4jX5MPNgKB4CFxxm1N"
+        );
+        assert_derive_keypair_eq!(
+            49370,
+            "20f84123a26e58dd32b0ea3c80381f35cd01bc22a20346cc65b0a67ae48532ba",
+            "B62qkiT4kgCawkSEF84ga5kP9QnhmTJEYzcfgGuk6okAJtSBfVcjm1M"
+        );
+        assert_derive_keypair_eq!(
+            12586,
+            "3414fc16e86e6ac272fda03cf8dcb4d7d47af91b4b726494dab43bf773ce1779",
+            "B62qoG5Yk4iVxpyczUrBNpwtx2xunhL48dydN53A2VjoRwF8NUTbVr4"
+        );
+    }
+
+    #[test]
+    fn derive_errors() {
+        let seed = hex::decode(TEST_SEED).expect("invalid seed hex");
+
+        assert_eq!(
+            super::derive_keypair(&seed, MAX_ACCOUNT + 1),
+            Err(Error::AccountIndex)
+        );
+        assert_eq!(super::derive_keypair(&seed[..8], 0), Err(Error::Seed));
+        assert!(super::derive_keypair(&seed, MAX_ACCOUNT).is_ok());
+    }
+}
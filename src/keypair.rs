@@ -4,13 +4,20 @@
 
 use core::fmt;
 
-use crate::{CurvePoint, FieldHelpers, PubKey, ScalarField, SecKey};
-use ark_ec::{AffineCurve, ProjectiveCurve};
-use ark_ff::UniformRand;
+use crate::{ct, CurvePoint, Error, FieldHelpers, Mnemonic, PubKey, ScalarField, SecKey};
+#[cfg(feature = "keyfile")]
+use crate::{secretbox, SecretBox};
+use ark_ff::{UniformRand, Zero};
 use rand::{self, CryptoRng, RngCore};
+#[cfg(feature = "serde")]
+use serde::{de, ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer};
+use zeroize::Zeroize;
 
 /// Keypair structure
-#[derive(Copy, Clone, PartialEq, Eq)]
+///
+/// Keypairs are not `Copy`, so that their secret key is not silently duplicated
+/// (see [SecKey]).
+#[derive(Clone, PartialEq, Eq)]
 pub struct Keypair {
     /// Secret key
     pub secret: SecKey,
@@ -20,6 +27,9 @@ pub struct Keypair {
 
 impl Keypair {
     /// Create a keypair from scalar field `secret` element and curve point `public`
+    ///
+    /// N.B. The keypair is not validated, see [Keypair::from_secret] and [Keypair::validate]
+    /// for checked alternatives.
     pub fn new(secret: ScalarField, public: CurvePoint) -> Self {
         Self {
             secret: SecKey::new(secret),
@@ -27,36 +37,169 @@ impl Keypair {
         }
     }
 
+    /// Create a keypair from secret key `secret`, deriving the corresponding public key
+    pub fn from_secret(secret: SecKey) -> Result<Self, Error> {
+        if secret.to_scalar().is_zero() {
+            return Err(Error::SecretKey);
+        }
+
+        let public: CurvePoint = ct::mul_generator(&secret.to_scalar());
+
+        Ok(Keypair {
+            secret,
+            public: PubKey::from_point(public)?,
+        })
+    }
+
     /// Generate a random keypair
     pub fn rand(rng: &mut (impl RngCore + CryptoRng)) -> Self {
-        let secret: ScalarField = ScalarField::rand(rng);
-        let public: CurvePoint = CurvePoint::prime_subgroup_generator()
-            .mul(secret)
-            .into_affine();
-
-        Keypair {
-            secret: SecKey::new(secret),
-            public: PubKey::new(public),
+        loop {
+            // Only fails (with negligible probability) for the zero scalar
+            if let Ok(kp) = Keypair::from_secret(SecKey::new(ScalarField::rand(rng))) {
+                return kp;
+            }
         }
     }
 
     /// Deserialize a keypair from secret key hex
-    pub fn from_hex(secret_hex: &str) -> Result<Self, &'static str> {
-        let mut bytes: Vec<u8> = hex::decode(secret_hex).map_err(|_| "Invalid secret key hex")?;
+    pub fn from_hex(secret_hex: &str) -> Result<Self, Error> {
+        let mut bytes: Vec<u8> = hex::decode(secret_hex).map_err(|_| Error::Hex)?;
         bytes.reverse(); // mina scalars hex format is in big-endian order
 
-        let secret = ScalarField::from_bytes(&bytes).map_err(|_| "Invalid secret key hex")?;
-        let public: CurvePoint = CurvePoint::prime_subgroup_generator()
-            .mul(secret)
-            .into_affine();
+        let secret = ScalarField::from_bytes(&bytes);
+        bytes.zeroize();
+
+        Keypair::from_secret(SecKey::new(secret?))
+    }
+
+    /// Deserialize a keypair from base58 encoded secret key (e.g. "EK...")
+    pub fn from_base58(secret_base58: &str) -> Result<Self, Error> {
+        Keypair::from_secret(SecKey::from_base58(secret_base58)?)
+    }
+
+    /// Recover the keypair for Mina account `account` from BIP39 mnemonic `phrase`
+    /// (see [Mnemonic] and [crate::hd])
+    pub fn from_mnemonic(phrase: &str, account: u32) -> Result<Self, Error> {
+        Mnemonic::from_phrase(phrase)?.to_keypair("", account)
+    }
+
+    /// Deserialize a keypair from Mina encrypted secret key file contents `json` (see [SecretBox])
+    /// using `password`
+    #[cfg(feature = "keyfile")]
+    pub fn from_encrypted_json(json: &str, password: &str) -> Result<Self, Error> {
+        let plaintext = SecretBox::from_json(json)?.decrypt(password.as_bytes())?;
 
-        Ok(Keypair::new(secret, public))
+        // The plaintext is the versioned secret key scalar (little-endian)
+        let secret = if plaintext.len() != 33 {
+            Err(Error::Length)
+        } else if plaintext[0] != 0x01 {
+            Err(Error::Version)
+        } else {
+            ScalarField::from_bytes(&plaintext[1..])
+        };
+
+        Keypair::from_secret(SecKey::new(secret?))
+    }
+
+    /// Serialize keypair's secret key into Mina encrypted secret key file contents (see [SecretBox])
+    /// using `password` and randomness from `rng`
+    #[cfg(feature = "keyfile")]
+    pub fn to_encrypted_json(
+        &self,
+        password: &str,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<String, Error> {
+        let mut plaintext: Vec<u8> = Vec::with_capacity(1 + 32);
+        plaintext.push(0x01); // private key version
+        let mut scalar_bytes = self.secret.to_scalar().to_bytes();
+        plaintext.extend(&scalar_bytes);
+        scalar_bytes.zeroize();
+
+        let secret_box =
+            SecretBox::encrypt(&plaintext, password.as_bytes(), secretbox::PW_DIFF, rng);
+        plaintext.zeroize();
+
+        Ok(secret_box?.to_json())
+    }
+
+    /// Check that the keypair's public key is valid (see [PubKey::validate]) and
+    /// corresponds to its secret key
+    pub fn validate(&self) -> Result<(), Error> {
+        self.public.validate()?;
+
+        let public: CurvePoint = ct::mul_generator(&self.secret.to_scalar());
+        if public != self.public.to_point() {
+            return Err(Error::KeypairMismatch);
+        }
+
+        Ok(())
     }
 
     /// Obtain the Mina address corresponding to the keypair's public key
-    pub fn get_address(self) -> String {
+    pub fn get_address(&self) -> String {
         self.public.to_address()
     }
+
+    /// Opt in to serializing the keypair's secret key (see [ExposeSecret])
+    #[cfg(feature = "serde")]
+    pub fn expose_secret(&self) -> ExposeSecret<'_> {
+        ExposeSecret(self)
+    }
+}
+
+/// Keypairs serialize only their public key, i.e. `{ "public": "B62q..." }`
+///
+/// Use [ExposeSecret] to also serialize the secret key.
+#[cfg(feature = "serde")]
+impl Serialize for Keypair {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Keypair", 1)?;
+        state.serialize_field("public", &self.public)?;
+        state.end()
+    }
+}
+
+/// Wrapper that serializes a keypair including its base58 encoded secret key, i.e.
+/// `{ "public": "B62q...", "secret": "EK..." }`, which is the form [Keypair] deserializes from
+#[cfg(feature = "serde")]
+pub struct ExposeSecret<'a>(pub &'a Keypair);
+
+#[cfg(feature = "serde")]
+impl Serialize for ExposeSecret<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut secret = self.0.secret.to_base58();
+
+        let mut state = serializer.serialize_struct("Keypair", 2)?;
+        let result = state
+            .serialize_field("public", &self.0.public)
+            .and_then(|_| state.serialize_field("secret", &secret));
+        secret.zeroize();
+        result?;
+
+        state.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Keypair {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Exposed {
+            public: PubKey,
+            secret: String,
+        }
+
+        let mut exposed = Exposed::deserialize(deserializer)?;
+        let kp = Keypair::from_base58(&exposed.secret);
+        exposed.secret.zeroize();
+
+        let kp = kp.map_err(de::Error::custom)?;
+        if kp.public != exposed.public {
+            return Err(de::Error::custom(Error::KeypairMismatch));
+        }
+
+        Ok(kp)
+    }
 }
 
 impl fmt::Debug for Keypair {
@@ -79,28 +222,169 @@ mod tests {
 
     #[test]
     fn from_hex() {
-        assert_eq!(Keypair::from_hex(""), Err("Invalid secret key hex"));
+        assert_eq!(Keypair::from_hex(""), Err(Error::Length));
         assert_eq!(
             Keypair::from_hex("1428fadcf0c02396e620f14f176fddb5d769b7de2027469d027a80142ef8f07"),
-            Err("Invalid secret key hex")
+            Err(Error::Hex)
         );
         assert_eq!(
             Keypair::from_hex("0f5314f176fddb5d769b7de2027469d027ad428fadcf0c02396e6280142efb7d8"),
-            Err("Invalid secret key hex")
+            Err(Error::Hex)
         );
         assert_eq!(
             Keypair::from_hex("g64244176fddb5d769b7de2027469d027ad428fadcf0c02396e6280142efb7d8"),
-            Err("Invalid secret key hex")
+            Err(Error::Hex)
         );
         assert_eq!(
             Keypair::from_hex("dd4244176fddb5d769b7de2027469d027ad428fadcc0c02396e6280142efb718"),
-            Err("Invalid secret key hex")
+            Err(Error::Field)
         );
 
         Keypair::from_hex("164244176fddb5d769b7de2027469d027ad428fadcc0c02396e6280142efb718")
             .expect("failed to decode keypair secret key");
     }
 
+    #[test]
+    fn from_secret() {
+        assert_eq!(
+            Keypair::from_secret(SecKey::new(ScalarField::zero())),
+            Err(Error::SecretKey)
+        );
+        assert_eq!(
+            Keypair::from_hex("0000000000000000000000000000000000000000000000000000000000000000"),
+            Err(Error::SecretKey)
+        );
+
+        let kp =
+            Keypair::from_hex("164244176fddb5d769b7de2027469d027ad428fadcc0c02396e6280142efb718")
+                .expect("failed to create keypair");
+        assert_eq!(Keypair::from_secret(kp.secret.clone()), Ok(kp));
+    }
+
+    #[test]
+    fn validate() {
+        let kp = Keypair::rand(&mut rand::rngs::OsRng);
+        assert_eq!(kp.validate(), Ok(()));
+
+        let other = Keypair::rand(&mut rand::rngs::OsRng);
+        assert_eq!(
+            Keypair::new(*kp.secret.to_scalar(), other.public.to_point()).validate(),
+            Err(Error::KeypairMismatch)
+        );
+        assert_eq!(
+            Keypair::new(*kp.secret.to_scalar(), CurvePoint::zero()).validate(),
+            Err(Error::PointAtInfinity)
+        );
+    }
+
+    #[test]
+    fn from_base58() {
+        assert_eq!(
+            Keypair::from_base58("EKDt66ubGg5SDiwcQABWfFZaruq6idcyrLLfyZQjoH4CN3PHEiNk"),
+            Err(Error::Checksum)
+        );
+
+        macro_rules! assert_from_base58_eq {
+            ($sec_key_base58:expr, $sec_key_hex:expr) => {
+                let kp = Keypair::from_base58($sec_key_base58).expect("failed to create keypair");
+                assert_eq!(
+                    kp,
+                    Keypair::from_hex($sec_key_hex).expect("failed to create keypair")
+                );
+                assert_eq!(kp.secret.to_base58(), $sec_key_base58);
+            };
+        }
+
+        assert_from_base58_eq!(
+            "EKDt66ubGg5SDiwcQABWfFZaruq6idcyrLLfyZQjoH4CN3PHEiNj",
+            "164244176fddb5d769b7de2027469d027ad428fadcc0c02396e6280142efb718"
+        );
+        assert_from_base58_eq!(
+            "EKE3W1XLtaAUXSKcpeh6J7ntqeeDrkjih3DVGM5Z3Hdb2wB2dtR2",
+            "3ca187a58f09da346844964310c7e0dd948a9105702b716f4d732e042e0c172e"
+        );
+        assert_from_base58_eq!(
+            "EKEaQv91GMtM91VsHrmh8wsdarZpNpPSpGCr8BCzyhbEwsrMQQWo",
+            "336eb4a19b3d8905824b0f2254fb495573be302c17582748bf7e101965aa4774"
+        );
+        assert_from_base58_eq!(
+            "EKEDGUnFazxGZFkVq5Ng2oUTZ7Vau7tqYMPCPzmW6BtKJBs3oWcL",
+            "1dee867358d4000f1dafa5978341fb515f89eeddbe450bd57df091f1e63d4444"
+        );
+    }
+
+    #[test]
+    fn from_mnemonic() {
+        let phrase = "course grief vintage slim tell hospital car maze model style elegant kitchen state purpose matrix gas grid enable frown road goddess glove canyon key";
+
+        assert_eq!(
+            Keypair::from_mnemonic(phrase, 0)
+                .expect("failed to recover keypair")
+                .get_address(),
+            "B62qnzbXmRNo9q32n4SNu2mpB8e7FYYLH8NmaX6oFCBYjjQ8SbD7uzV"
+        );
+        assert_eq!(
+            Keypair::from_mnemonic(phrase, 3)
+                .expect("failed to recover keypair")
+                .get_address(),
+            "B62qoqiAgERjCjXhofXiD7cMLJSKD8hE8ZtMh4jX5MPNgKB4CFxxm1N"
+        );
+        assert_eq!(
+            Keypair::from_mnemonic("course grief vintage", 0),
+            Err(Error::MnemonicWordCount)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "keyfile")]
+    fn from_encrypted_json() {
+        // Produced with libsodium using Mina's default password hashing difficulty
+        let json = r#"{"box_primitive":"xsalsa20poly1305","pw_primitive":"argon2i","nonce":"6zErzh9TCQK3UDuyiyLB75UpCtzarHacK7wr2aX","pwsalt":"AiXWDwM94Vic3uecHxA3BavS2eTU","pwdiff":[134217728,6],"ciphertext":"CEHTZuW8EsfGzLL5gy82xb8NsTbzbCBPUoFU4fBMK3gLYSuEQ1oy6ArAcVHhWwcoifBN2hceg"}"#;
+
+        let kp = Keypair::from_encrypted_json(json, "naughty blue worm")
+            .expect("failed to decrypt keypair");
+        assert_eq!(
+            kp.get_address(),
+            "B62qnzbXmRNo9q32n4SNu2mpB8e7FYYLH8NmaX6oFCBYjjQ8SbD7uzV"
+        );
+
+        assert_eq!(
+            Keypair::from_encrypted_json("{}", "naughty blue worm"),
+            Err(Error::Json)
+        );
+    }
+
+    // Key file written by the Mina OCaml tooling (see tests/vectors/README.md)
+    #[test]
+    #[cfg(feature = "keyfile")]
+    #[ignore = "requires tests/vectors/mina-keypair generated with the Mina OCaml tooling"]
+    fn from_ocaml_key_file() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/vectors/mina-keypair");
+        let json = std::fs::read_to_string(path).expect("failed to read key file");
+        let address =
+            std::fs::read_to_string(format!("{}.pub", path)).expect("failed to read address");
+
+        let kp = Keypair::from_encrypted_json(&json, "naughty blue worm")
+            .expect("failed to decrypt keypair");
+        assert_eq!(kp.get_address(), address.trim());
+    }
+
+    #[test]
+    #[cfg(feature = "keyfile")]
+    fn to_encrypted_json() {
+        let kp =
+            Keypair::from_hex("3ca187a58f09da346844964310c7e0dd948a9105702b716f4d732e042e0c172e")
+                .expect("failed to create keypair");
+        let json = kp
+            .to_encrypted_json("hunter2", &mut rand::rngs::OsRng)
+            .expect("failed to encrypt keypair");
+
+        assert_eq!(
+            Keypair::from_encrypted_json(&json, "hunter2").expect("failed to decrypt keypair"),
+            kp
+        );
+    }
+
     #[test]
     fn get_address() {
         macro_rules! assert_get_address_eq {
@@ -135,4 +419,37 @@ mod tests {
             "B62qoG5Yk4iVxpyczUrBNpwtx2xunhL48dydN53A2VjoRwF8NUTbVr4"
         );
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        let kp =
+            Keypair::from_hex("164244176fddb5d769b7de2027469d027ad428fadcc0c02396e6280142efb718")
+                .expect("failed to create keypair");
+
+        // Secret key is omitted by default
+        assert_eq!(
+            serde_json::to_string(&kp).expect("failed to serialize"),
+            r#"{"public":"B62qnzbXmRNo9q32n4SNu2mpB8e7FYYLH8NmaX6oFCBYjjQ8SbD7uzV"}"#
+        );
+
+        let json = r#"{"public":"B62qnzbXmRNo9q32n4SNu2mpB8e7FYYLH8NmaX6oFCBYjjQ8SbD7uzV","secret":"EKDt66ubGg5SDiwcQABWfFZaruq6idcyrLLfyZQjoH4CN3PHEiNj"}"#;
+        assert_eq!(
+            serde_json::to_string(&kp.expose_secret()).expect("failed to serialize"),
+            json
+        );
+        assert_eq!(
+            serde_json::from_str::<Keypair>(json).expect("failed to deserialize"),
+            kp
+        );
+
+        assert!(serde_json::from_str::<Keypair>(
+            r#"{"public":"B62qnzbXmRNo9q32n4SNu2mpB8e7FYYLH8NmaX6oFCBYjjQ8SbD7uzV"}"#
+        )
+        .is_err());
+        assert!(serde_json::from_str::<Keypair>(
+            r#"{"public":"B62qicipYxyEHu7QjUqS7QvBipTs5CzgkYZZZkPoKVYBu6tnDUcE9Zt","secret":"EKDt66ubGg5SDiwcQABWfFZaruq6idcyrLLfyZQjoH4CN3PHEiNj"}"#
+        )
+        .is_err());
+    }
 }
@@ -6,6 +6,7 @@
 //!
 //! ```
 //! use rand;
+//! use std::borrow::Cow;
 //! use mina_signer::{Hashable, Keypair, NetworkId, ROInput, Signable, Signer};
 //!
 //! #[derive(Clone, Copy)]
@@ -26,10 +27,11 @@
 //! }
 //!
 //! impl Signable for Thing {
-//!     fn domain_string(network_id: NetworkId) -> &'static str {
+//!     fn domain_string(network_id: &NetworkId) -> Cow<'static, str> {
 //!        match network_id {
-//!            NetworkId::MAINNET => "ThingSigMainnet",
-//!            NetworkId::TESTNET => "ThingSigTestnet",
+//!            NetworkId::MAINNET => "ThingSigMainnet".into(),
+//!            NetworkId::TESTNET => "ThingSigTestnet".into(),
+//!            NetworkId::Custom(name) => format!("ThingSig{}", name).into(),
 //!        }
 //!    }
 //! }
@@ -38,47 +40,173 @@
 //! let thang = Thing { foo: 31, bar: 45 };
 //!
 //! let mut ctx = mina_signer::create(NetworkId::TESTNET);
-//! let sig = ctx.sign(kp, thang);
+//! let sig = ctx.sign(&kp, thang).expect("failed to sign");
 //! assert_eq!(ctx.verify(sig, kp.public, thang), true);
 //! ```
 
+pub mod ct;
 pub mod domain;
+pub mod error;
+pub mod hash;
+pub mod hd;
 pub mod keypair;
+pub mod mnemonic;
 pub mod pubkey;
 pub mod roinput;
 pub mod schnorr;
 pub mod seckey;
+#[cfg(feature = "keyfile")]
+pub mod secretbox;
 pub mod signature;
+#[cfg(feature = "parallel")]
+pub mod vanity;
 
 pub use domain::{BaseField, CurvePoint, FieldHelpers, ScalarField};
+pub use error::Error;
 pub use keypair::Keypair;
+pub use mnemonic::Mnemonic;
 pub use pubkey::{CompressedPubKey, PubKey};
 pub use roinput::ROInput;
 pub use schnorr::Schnorr;
 pub use seckey::SecKey;
-pub use signature::Signature;
+#[cfg(feature = "keyfile")]
+pub use secretbox::SecretBox;
+pub use signature::{Signature, VerifyError};
 
 use oracle::{
     pasta,
     poseidon::{
-        ArithmeticSponge, ArithmeticSpongeParams, PlonkSpongeConstantsBasic, Sponge,
-        SpongeConstants,
+        ArithmeticSponge, ArithmeticSpongeParams, PlonkSpongeConstants15W,
+        PlonkSpongeConstantsBasic, Sponge, SpongeConstants,
     },
 };
+use rand::{CryptoRng, RngCore};
+use std::{borrow::Cow, fmt, str::FromStr};
 
 /// Mina network (or blockchain) identifier
-#[derive(Copy, Clone)]
+///
+/// Networks are identified by name (see [NetworkId::from_str] and [NetworkId::name]), where
+/// `"devnet"` is an alias of `"testnet"` and other names are custom networks (see
+/// [NetworkId::custom]).  Serializes as its name (with the `serde` feature).
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum NetworkId {
     /// Id for all testnets
-    TESTNET = 0x00,
+    TESTNET,
 
     /// Id for mainnet
-    MAINNET = 0x01,
+    MAINNET,
+
+    /// Id for a custom network with the given name (see [NetworkId::custom])
+    Custom(CustomNetworkId),
 }
 
-impl From<NetworkId> for u8 {
-    fn from(id: NetworkId) -> u8 {
-        id as u8
+/// Name of a custom network, i.e. a non-empty ASCII name other than those of the standard
+/// networks
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CustomNetworkId(String);
+
+impl CustomNetworkId {
+    /// Get the network name
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for CustomNetworkId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl NetworkId {
+    /// Create the id of the network called `name`
+    ///
+    /// The standard network names (`"mainnet"`, `"testnet"` and `"devnet"`) give the standard
+    /// network ids, while other names must be non-empty and ASCII.
+    ///
+    /// **Example**
+    ///
+    /// ```
+    /// use mina_signer::NetworkId;
+    ///
+    /// let network_id = NetworkId::custom("berkeley").expect("invalid network");
+    /// assert_eq!(network_id.name(), "berkeley");
+    /// assert_eq!(NetworkId::custom("devnet"), Ok(NetworkId::TESTNET));
+    /// ```
+    pub fn custom(name: &str) -> Result<Self, Error> {
+        match name {
+            "testnet" | "devnet" => Ok(NetworkId::TESTNET),
+            "mainnet" => Ok(NetworkId::MAINNET),
+            name if name.is_empty() || !name.is_ascii() => Err(Error::NetworkId),
+            name => Ok(NetworkId::Custom(CustomNetworkId(name.to_string()))),
+        }
+    }
+
+    /// Get the network name
+    pub fn name(&self) -> &str {
+        match self {
+            NetworkId::TESTNET => "testnet",
+            NetworkId::MAINNET => "mainnet",
+            NetworkId::Custom(name) => name.as_str(),
+        }
+    }
+
+    /// Get the network id bytes mixed into the nonce derivation
+    ///
+    /// As per Mina's convention these are the single bytes `0x00` for testnet and `0x01` for
+    /// mainnet, and the network name for custom networks.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            NetworkId::TESTNET => vec![0x00],
+            NetworkId::MAINNET => vec![0x01],
+            NetworkId::Custom(name) => name.as_str().as_bytes().to_vec(),
+        }
+    }
+
+    /// Get the domain string of Mina signatures (e.g. of transactions) on this network
+    ///
+    /// As per Mina's convention custom networks use the network name followed by `Signature`,
+    /// truncated to the maximum domain string length of 20.
+    pub fn signature_domain(&self) -> Cow<'static, str> {
+        match self {
+            NetworkId::TESTNET => "CodaSignature".into(),
+            NetworkId::MAINNET => "MinaSignatureMainnet".into(),
+            NetworkId::Custom(name) => {
+                let mut domain = format!("{}Signature", name);
+                domain.truncate(20);
+                domain.into()
+            }
+        }
+    }
+}
+
+impl FromStr for NetworkId {
+    type Err = Error;
+
+    /// Parse network name (e.g. `"mainnet"`, `"testnet"`, `"devnet"` or a custom network name)
+    fn from_str(name: &str) -> Result<Self, Error> {
+        NetworkId::custom(name)
+    }
+}
+
+impl fmt::Display for NetworkId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for NetworkId {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for NetworkId {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        NetworkId::from_str(&name).map_err(serde::de::Error::custom)
     }
 }
 
@@ -96,6 +224,7 @@ pub trait Hashable: Copy {
 ///
 /// ```
 /// use mina_signer::{Hashable, NetworkId, ROInput, Signable};
+/// use std::borrow::Cow;
 ///
 /// #[derive(Clone, Copy)]
 /// struct Example;
@@ -109,10 +238,11 @@ pub trait Hashable: Copy {
 /// }
 ///
 /// impl Signable for Example {
-///     fn domain_string(network_id: NetworkId) -> &'static str {
+///     fn domain_string(network_id: &NetworkId) -> Cow<'static, str> {
 ///        match network_id {
-///            NetworkId::MAINNET => "ExampleSigMainnet",
-///            NetworkId::TESTNET => "ExampleSigTestnet",
+///            NetworkId::MAINNET => "ExampleSigMainnet".into(),
+///            NetworkId::TESTNET => "ExampleSigTestnet".into(),
+///            NetworkId::Custom(name) => format!("ExampleSig{}", name).into(),
 ///        }
 ///    }
 /// }
@@ -122,18 +252,61 @@ pub trait Hashable: Copy {
 pub trait Signable: Hashable {
     /// Returns the unique domain string for this input type on network specified by `network_id`.
     ///
-    /// The domain string length must be `<= 20`.
-    fn domain_string(network_id: NetworkId) -> &'static str;
+    /// The domain string must be ASCII with length `<= 20`, otherwise signing and verification
+    /// fail with [Error::Domain].
+    fn domain_string(network_id: &NetworkId) -> Cow<'static, str>;
 }
 
 /// Signer interface for signing [Signable] inputs and verifying [Signatures](Signature) using [Keypairs](Keypair) and [PubKeys](PubKey)
 pub trait Signer {
     /// Sign `input` (see [Signable]) using keypair `kp` and return the corresponding signature.
-    fn sign<S: Signable>(&mut self, kp: Keypair, input: S) -> Signature;
+    fn sign<S: Signable>(&mut self, kp: &Keypair, input: S) -> Result<Signature, Error>;
+
+    /// Sign `input` (see [Signable]) using keypair `kp` and return the corresponding signature,
+    /// mixing randomness from `rng` into the otherwise deterministic nonce derivation.
+    ///
+    /// This hedges against fault injection attacks on the signer at the cost of signatures
+    /// no longer being reproducible.  The resulting signatures verify exactly like those
+    /// returned by [Signer::sign].
+    fn sign_with_rng<S: Signable>(
+        &mut self,
+        kp: &Keypair,
+        input: S,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<Signature, Error>;
+
+    /// Sign all `inputs` (see [Signable]) using keypair `kp` in parallel and return the corresponding
+    /// signatures in input order.
+    ///
+    /// The signatures are identical to those returned by calling [Signer::sign] on each input in turn.
+    /// Each worker thread signs with its own copy of the signer context.
+    #[cfg(feature = "parallel")]
+    fn sign_many<S: Signable + Sync>(
+        &mut self,
+        kp: &Keypair,
+        inputs: &[S],
+    ) -> Result<Vec<Signature>, Error>
+    where
+        Self: Clone + Sync;
 
     /// Verify that the signature `sig` on `input` (see [Signable]) is signed with the secret key corresponding to `pub_key`.
     /// Return `true` if the signature is valid and `false` otherwise.
     fn verify<S: Signable>(&mut self, sig: Signature, pub_key: PubKey, input: S) -> bool;
+
+    /// Verify that the signature `sig` on `input` (see [Signable]) is signed with the secret key corresponding to `pub_key`.
+    /// Return `Ok(())` if the signature is valid and otherwise the reason it is invalid (see [VerifyError]),
+    /// including whether it would be valid on the other network.
+    fn verify_detailed<S: Signable>(
+        &mut self,
+        sig: Signature,
+        pub_key: PubKey,
+        input: S,
+    ) -> Result<(), VerifyError>;
+
+    /// Verify a `batch` of signatures, public keys and inputs (see [Signer::verify]) at once,
+    /// which is considerably faster than verifying them one by one.
+    /// Return for each item `true` if its signature is valid and `false` otherwise.
+    fn verify_batch<S: Signable>(&mut self, batch: &[(Signature, PubKey, S)]) -> Vec<bool>;
 }
 
 /// Create a default signer context for network instance identified by `network_id`
@@ -145,13 +318,33 @@ pub trait Signer {
 ///
 /// let mut ctx = mina_signer::create(NetworkId::MAINNET);
 /// ```
-pub fn create(network_id: NetworkId) -> impl Signer {
+pub fn create(network_id: NetworkId) -> impl Signer + Clone {
     Schnorr::<PlonkSpongeConstantsBasic>::new(
         ArithmeticSponge::<BaseField, PlonkSpongeConstantsBasic>::new(pasta::fp::params()),
         network_id,
     )
 }
 
+/// Create a kimchi signer context for network instance identified by `network_id`
+///
+/// Kimchi signatures are those of Mina since the Berkeley hard fork (e.g. compatible with o1js
+/// `Signature.create`), which hash the packed random oracle input (see [ROInput]) with the
+/// kimchi Poseidon parameters.
+///
+/// **Example**
+///
+/// ```
+/// use mina_signer::NetworkId;
+///
+/// let mut ctx = mina_signer::create_kimchi(NetworkId::MAINNET);
+/// ```
+pub fn create_kimchi(network_id: NetworkId) -> impl Signer + Clone {
+    Schnorr::<PlonkSpongeConstants15W>::new_kimchi(
+        ArithmeticSponge::<BaseField, PlonkSpongeConstants15W>::new(pasta::fp_3::params()),
+        network_id,
+    )
+}
+
 /// Create a custom signer context for network instance identified by `network_id` using custom sponge parameters `params`
 ///
 /// **Example**
@@ -0,0 +1,152 @@
+//! Mnemonic structures and helpers
+//!
+//! BIP39 mnemonic (backup phrase) generation and recovery of the seed from which
+//! keypairs are derived (see [crate::hd]).  Only the English wordlist is supported,
+//! as used by the Auro and Clorio wallets.
+
+use bip39::Language;
+use rand::{CryptoRng, RngCore};
+use zeroize::Zeroizing;
+
+use crate::{hd, Error, Keypair};
+
+/// BIP39 mnemonic
+pub struct Mnemonic(bip39::Mnemonic); // No Debug nor Display
+
+impl Mnemonic {
+    /// Generate a random mnemonic of `word_count` words (12, 15, 18, 21 or 24) using
+    /// randomness from `rng`
+    pub fn generate(
+        word_count: usize,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<Self, Error> {
+        bip39::Mnemonic::generate_in_with(rng, Language::English, word_count)
+            .map(Mnemonic)
+            .map_err(map_error)
+    }
+
+    /// Parse mnemonic `phrase`, validating its word count, wordlist and checksum
+    pub fn from_phrase(phrase: &str) -> Result<Self, Error> {
+        bip39::Mnemonic::parse_in(Language::English, phrase)
+            .map(Mnemonic)
+            .map_err(map_error)
+    }
+
+    /// Serialize mnemonic into its phrase
+    pub fn to_phrase(&self) -> String {
+        self.0.to_string()
+    }
+
+    /// Number of words in the mnemonic
+    pub fn word_count(&self) -> usize {
+        self.0.word_count()
+    }
+
+    /// Recover the BIP39 seed from the mnemonic and (optional, possibly empty) `passphrase`
+    ///
+    /// The seed is zeroized when dropped.
+    pub fn to_seed(&self, passphrase: &str) -> Zeroizing<[u8; 64]> {
+        Zeroizing::new(self.0.to_seed(passphrase))
+    }
+
+    /// Derive the keypair for Mina account `account` (see [crate::hd]) from the mnemonic
+    /// and (optional, possibly empty) `passphrase`
+    pub fn to_keypair(&self, passphrase: &str, account: u32) -> Result<Keypair, Error> {
+        hd::derive_keypair(&*self.to_seed(passphrase), account)
+    }
+}
+
+fn map_error(err: bip39::Error) -> Error {
+    match err {
+        bip39::Error::BadWordCount(_) | bip39::Error::BadEntropyBitCount(_) => {
+            Error::MnemonicWordCount
+        }
+        bip39::Error::UnknownWord(_) => Error::MnemonicWord,
+        bip39::Error::InvalidChecksum => Error::MnemonicChecksum,
+        _ => Error::Mnemonic,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_PHRASE: &str = "course grief vintage slim tell hospital car maze model style elegant kitchen state purpose matrix gas grid enable frown road goddess glove canyon key";
+
+    #[test]
+    fn from_phrase() {
+        let mnemonic = Mnemonic::from_phrase(TEST_PHRASE).expect("failed to parse mnemonic");
+        assert_eq!(mnemonic.word_count(), 24);
+        assert_eq!(mnemonic.to_phrase(), TEST_PHRASE);
+
+        assert_eq!(
+            Mnemonic::from_phrase("course grief vintage").map(|_| ()),
+            Err(Error::MnemonicWordCount)
+        );
+        assert_eq!(
+            Mnemonic::from_phrase("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon mina").map(|_| ()),
+            Err(Error::MnemonicWord)
+        );
+        assert_eq!(
+            Mnemonic::from_phrase("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon").map(|_| ()),
+            Err(Error::MnemonicChecksum)
+        );
+    }
+
+    #[test]
+    fn to_seed() {
+        macro_rules! assert_to_seed_eq {
+            ($phrase:expr, $passphrase:expr, $target_seed:expr) => {
+                let mnemonic = Mnemonic::from_phrase($phrase).expect("failed to parse mnemonic");
+                assert_eq!(hex::encode(&*mnemonic.to_seed($passphrase)), $target_seed);
+            };
+        }
+
+        // BIP39 reference test vectors
+        assert_to_seed_eq!(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+            "TREZOR",
+            "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04"
+        );
+        assert_to_seed_eq!(
+            "legal winner thank year wave sausage worth useful legal winner thank yellow",
+            "TREZOR",
+            "2e8905819b8723fe2c1d161860e5ee1830318dbf49a83bd451cfb8440c28bd6fa457fe1296106559a3c80937a1c1069be3a3a5bd381ee6260e8d9739fce1f607"
+        );
+        assert_to_seed_eq!(
+            TEST_PHRASE,
+            "",
+            "1bac2a4281e6095c3945cdefef36261d1af600f7b1a0d7fd473f3acfb7c614326261589ee860375c5c977f2ba93bd67d03bd502fda50004c787588ec57d8f0fb"
+        );
+    }
+
+    #[test]
+    fn to_keypair() {
+        let mnemonic = Mnemonic::from_phrase(TEST_PHRASE).expect("failed to parse mnemonic");
+        let kp = mnemonic
+            .to_keypair("", 1)
+            .expect("failed to derive keypair");
+        assert_eq!(
+            kp.get_address(),
+            "B62qicipYxyEHu7QjUqS7QvBipTs5CzgkYZZZkPoKVYBu6tnDUcE9Zt"
+        );
+    }
+
+    #[test]
+    fn generate() {
+        for word_count in [12, 24] {
+            let mnemonic = Mnemonic::generate(word_count, &mut rand::rngs::OsRng)
+                .expect("failed to generate mnemonic");
+            assert_eq!(mnemonic.word_count(), word_count);
+
+            let recovered =
+                Mnemonic::from_phrase(&mnemonic.to_phrase()).expect("failed to parse mnemonic");
+            assert_eq!(*recovered.to_seed(""), *mnemonic.to_seed(""));
+        }
+
+        assert_eq!(
+            Mnemonic::generate(13, &mut rand::rngs::OsRng).map(|_| ()),
+            Err(Error::MnemonicWordCount)
+        );
+    }
+}
@@ -2,63 +2,101 @@
 //!
 //! Definition of public key structure and helpers
 
-use ark_ff::{BigInteger, PrimeField};
+use ark_ff::{BigInteger, PrimeField, Zero};
 use bs58;
 use core::fmt;
+#[cfg(feature = "serde")]
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use sha2::{Digest, Sha256};
 use std::ops::Neg;
 
-use crate::{BaseField, CurvePoint, FieldHelpers};
+use crate::{BaseField, CurvePoint, Error, FieldHelpers};
 
 /// Length of Mina addresses
 pub const MINA_ADDRESS_LEN: usize = 55;
 
+/// Length of uncompressed public key bytes (see [PubKey::to_bytes])
+pub const PUB_KEY_BYTES_LEN: usize = 64;
+
+/// Length of compressed public key bytes (see [PubKey::to_compressed_bytes])
+pub const COMPRESSED_PUB_KEY_BYTES_LEN: usize = 33;
+
 /// Public key
 #[derive(Copy, Clone, fmt::Debug, PartialEq, Eq)]
 pub struct PubKey(CurvePoint);
 
 impl PubKey {
     /// Create a public key from curve point
+    ///
+    /// N.B. The point is not validated, see [PubKey::from_point] for a checked alternative.
     pub fn new(point: CurvePoint) -> Self {
         Self(point)
     }
 
-    /// Deserialize Mina address into public key
-    pub fn from_address(address: &str) -> Result<Self, &'static str> {
-        if address.len() != MINA_ADDRESS_LEN {
-            return Err("Invalid address length");
-        }
+    /// Create a public key from curve point, checking that it is a valid public key
+    /// (see [PubKey::validate])
+    pub fn from_point(point: CurvePoint) -> Result<Self, Error> {
+        let pub_key = Self(point);
+        pub_key.validate()?;
 
-        let bytes = bs58::decode(address)
-            .into_vec()
-            .map_err(|_| "Invalid address encoding")?;
+        Ok(pub_key)
+    }
 
-        let (raw, checksum) = (&bytes[..bytes.len() - 4], &bytes[bytes.len() - 4..]);
-        let hash = Sha256::digest(&Sha256::digest(raw)[..]);
-        if checksum != &hash[..4] {
-            return Err("Invalid address checksum");
+    /// Check that the public key is a non-identity point on the curve
+    ///
+    /// N.B. The Pallas curve has cofactor 1, so every point on the curve is in the
+    /// prime order subgroup.
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.0.is_zero() {
+            return Err(Error::PointAtInfinity);
         }
-
-        let (version, x_bytes, y_parity) = (
-            &raw[..3],
-            &raw[3..bytes.len() - 5],
-            raw[bytes.len() - 5] == 0x01,
-        );
-        if version != [0xcb, 0x01, 0x01] {
-            return Err("Invalid address version info");
+        if !self.0.is_on_curve() {
+            return Err(Error::PointNotOnCurve);
         }
 
-        let x = BaseField::from_bytes(x_bytes).map_err(|_| "invalid x-coordinate bytes")?;
-        let mut pt =
-            CurvePoint::get_point_from_x(x, y_parity).ok_or("Invalid address x-coordinate")?;
+        Ok(())
+    }
 
-        if pt.y.into_repr().is_even() == y_parity {
-            pt.y = pt.y.neg();
-        }
+    /// Deserialize Mina address into public key
+    pub fn from_address(address: &str) -> Result<Self, Error> {
+        let compressed = CompressedPubKey::from_address(address)?;
+        let pt = decompress(compressed.x, compressed.is_odd).ok_or(Error::XCoordinate)?;
 
         Ok(PubKey::new(pt))
     }
 
+    /// Deserialize public key from hex (see [PubKey::from_bytes])
+    pub fn from_hex(public_hex: &str) -> Result<Self, Error> {
+        let bytes: Vec<u8> = hex::decode(public_hex).map_err(|_| Error::Hex)?;
+
+        PubKey::from_bytes(&bytes)
+    }
+
+    /// Deserialize public key from bytes, either uncompressed (see [PubKey::to_bytes]) or
+    /// compressed (see [PubKey::to_compressed_bytes]), checking that it is a valid public key
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        match bytes.len() {
+            PUB_KEY_BYTES_LEN => {
+                let x = field_from_be_bytes(&bytes[..32])?;
+                let y = field_from_be_bytes(&bytes[32..])?;
+
+                PubKey::from_point(CurvePoint::new(x, y, false))
+            }
+            COMPRESSED_PUB_KEY_BYTES_LEN => {
+                let x = field_from_be_bytes(&bytes[..32])?;
+                let is_odd = match bytes[32] {
+                    0x00 => false,
+                    0x01 => true,
+                    _ => return Err(Error::Parity),
+                };
+
+                let pt = decompress(x, is_odd).ok_or(Error::XCoordinate)?;
+                PubKey::from_point(pt)
+            }
+            _ => Err(Error::Length),
+        }
+    }
+
     /// Convert public key into curve point
     pub fn to_point(self) -> CurvePoint {
         self.0
@@ -78,22 +116,65 @@ impl PubKey {
         let point = self.to_point();
         to_address(point.x, point.y.into_repr().is_odd())
     }
-}
 
-impl fmt::Display for PubKey {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    /// Serialize public key into uncompressed bytes
+    /// (64 bytes: big-endian x-coordinate followed by big-endian y-coordinate)
+    pub fn to_bytes(self) -> Vec<u8> {
         let point = self.to_point();
         let mut x_bytes = point.x.to_bytes();
         let mut y_bytes = point.y.to_bytes();
         x_bytes.reverse();
         y_bytes.reverse();
 
-        write!(f, "{}{}", hex::encode(x_bytes), hex::encode(y_bytes))
+        x_bytes.extend(y_bytes);
+
+        x_bytes
+    }
+
+    /// Serialize public key into compressed bytes
+    /// (33 bytes: big-endian x-coordinate followed by y-coordinate parity byte 0x00 or 0x01)
+    pub fn to_compressed_bytes(self) -> Vec<u8> {
+        let point = self.to_point();
+        let mut bytes = point.x.to_bytes();
+        bytes.reverse();
+
+        bytes.push(point.y.into_repr().is_odd() as u8);
+
+        bytes
+    }
+
+    /// Serialize public key into hex (see [PubKey::to_bytes])
+    pub fn to_hex(self) -> String {
+        hex::encode(self.to_bytes())
+    }
+}
+
+impl fmt::Display for PubKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_hex())
+    }
+}
+
+/// Public keys serialize as Mina addresses
+#[cfg(feature = "serde")]
+impl Serialize for PubKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_address())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for PubKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let address = String::deserialize(deserializer)?;
+        PubKey::from_address(&address).map_err(de::Error::custom)
     }
 }
 
 /// Compressed public keys consist of x-coordinate and y-coordinate parity.
-#[derive(Clone, Copy)]
+///
+/// Compressed public keys are ordered by x-coordinate and then parity.
+#[derive(Clone, Copy, fmt::Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct CompressedPubKey {
     /// X-coordinate
     pub x: BaseField,
@@ -102,6 +183,25 @@ pub struct CompressedPubKey {
     pub is_odd: bool,
 }
 
+// Recover curve point from x-coordinate and y-coordinate parity
+pub(crate) fn decompress(x: BaseField, is_odd: bool) -> Option<CurvePoint> {
+    let mut pt = CurvePoint::get_point_from_x(x, is_odd)?;
+
+    if pt.y.into_repr().is_even() == is_odd {
+        pt.y = pt.y.neg();
+    }
+
+    Some(pt)
+}
+
+// Deserialize base field element from big-endian bytes
+fn field_from_be_bytes(bytes: &[u8]) -> Result<BaseField, Error> {
+    let mut bytes = bytes.to_vec();
+    bytes.reverse();
+
+    BaseField::from_bytes(&bytes)
+}
+
 fn to_address(x: BaseField, is_odd: bool) -> String {
     let mut raw: Vec<u8> = vec![
         0xcb, // version for base58 check
@@ -123,15 +223,154 @@ fn to_address(x: BaseField, is_odd: bool) -> String {
 }
 
 impl CompressedPubKey {
+    /// Deserialize Mina address into compressed public key
+    ///
+    /// N.B. The x-coordinate is not checked to be on the curve, see [CompressedPubKey::decompress].
+    pub fn from_address(address: &str) -> Result<Self, Error> {
+        if address.len() != MINA_ADDRESS_LEN {
+            return Err(Error::Length);
+        }
+
+        let bytes = bs58::decode(address)
+            .into_vec()
+            .map_err(|_| Error::Base58)?;
+
+        let (raw, checksum) = (&bytes[..bytes.len() - 4], &bytes[bytes.len() - 4..]);
+        let hash = Sha256::digest(&Sha256::digest(raw)[..]);
+        if checksum != &hash[..4] {
+            return Err(Error::Checksum);
+        }
+
+        let (version, x_bytes, y_parity) = (
+            &raw[..3],
+            &raw[3..bytes.len() - 5],
+            raw[bytes.len() - 5] == 0x01,
+        );
+        if version != [0xcb, 0x01, 0x01] {
+            return Err(Error::Version);
+        }
+
+        let x = BaseField::from_bytes(x_bytes)?;
+
+        Ok(CompressedPubKey {
+            x,
+            is_odd: y_parity,
+        })
+    }
+
+    /// Decompress into public key, checking that it is a valid public key
+    pub fn decompress(self) -> Result<PubKey, Error> {
+        let pt = decompress(self.x, self.is_odd).ok_or(Error::XCoordinate)?;
+
+        PubKey::from_point(pt)
+    }
+
     /// Serialize compressed public key into corresponding Mina address
     pub fn to_address(self) -> String {
         to_address(self.x, self.is_odd)
     }
 }
 
+/// Compressed public keys serialize as Mina addresses
+#[cfg(feature = "serde")]
+impl Serialize for CompressedPubKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_address())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for CompressedPubKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let address = String::deserialize(deserializer)?;
+        CompressedPubKey::from_address(&address).map_err(de::Error::custom)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ark_ec::AffineCurve;
+
+    #[test]
+    fn from_point() {
+        assert_eq!(
+            PubKey::from_point(CurvePoint::zero()),
+            Err(Error::PointAtInfinity)
+        );
+        assert_eq!(
+            PubKey::from_point(CurvePoint::new(
+                BaseField::from(1u64),
+                BaseField::from(2u64),
+                false
+            )),
+            Err(Error::PointNotOnCurve)
+        );
+        assert_eq!(
+            PubKey::from_point(CurvePoint::prime_subgroup_generator()),
+            Ok(PubKey::new(CurvePoint::prime_subgroup_generator()))
+        );
+
+        let pk = PubKey::from_address("B62qnzbXmRNo9q32n4SNu2mpB8e7FYYLH8NmaX6oFCBYjjQ8SbD7uzV")
+            .expect("failed to create pubkey");
+        assert_eq!(PubKey::from_point(pk.to_point()), Ok(pk));
+    }
+
+    #[test]
+    fn from_hex() {
+        assert_eq!(PubKey::from_hex(""), Err(Error::Length));
+        assert_eq!(PubKey::from_hex("0g"), Err(Error::Hex));
+        assert_eq!(
+            PubKey::from_hex("0000000000000000000000000000000000000000000000000000000000000001000000000000000000000000000000000000000000000000000000000000000a"),
+            Err(Error::PointNotOnCurve)
+        );
+        assert_eq!(
+            PubKey::from_hex("ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff000000000000000000000000000000000000000000000000000000000000000a"),
+            Err(Error::Field)
+        );
+
+        macro_rules! assert_from_hex_check {
+            ($address:expr) => {
+                let pk = PubKey::from_address($address).expect("failed to create pubkey");
+                assert_eq!(PubKey::from_hex(&pk.to_string()), Ok(pk));
+                assert_eq!(PubKey::from_hex(&pk.to_hex()), Ok(pk));
+            };
+        }
+
+        assert_from_hex_check!("B62qnzbXmRNo9q32n4SNu2mpB8e7FYYLH8NmaX6oFCBYjjQ8SbD7uzV");
+        assert_from_hex_check!("B62qicipYxyEHu7QjUqS7QvBipTs5CzgkYZZZkPoKVYBu6tnDUcE9Zt");
+        assert_from_hex_check!("B62qoG5Yk4iVxpyczUrBNpwtx2xunhL48dydN53A2VjoRwF8NUTbVr4");
+    }
+
+    #[test]
+    fn from_bytes() {
+        macro_rules! assert_from_bytes_check {
+            ($address:expr) => {
+                let pk = PubKey::from_address($address).expect("failed to create pubkey");
+
+                let bytes = pk.to_bytes();
+                assert_eq!(bytes.len(), PUB_KEY_BYTES_LEN);
+                assert_eq!(PubKey::from_bytes(&bytes), Ok(pk));
+
+                let compressed_bytes = pk.to_compressed_bytes();
+                assert_eq!(compressed_bytes.len(), COMPRESSED_PUB_KEY_BYTES_LEN);
+                assert_eq!(&compressed_bytes[..32], &bytes[..32]);
+                assert_eq!(PubKey::from_bytes(&compressed_bytes), Ok(pk));
+            };
+        }
+
+        assert_from_bytes_check!("B62qnzbXmRNo9q32n4SNu2mpB8e7FYYLH8NmaX6oFCBYjjQ8SbD7uzV");
+        assert_from_bytes_check!("B62qrKG4Z8hnzZqp1AL8WsQhQYah3quN1qUj3SyfJA8Lw135qWWg1mi");
+        assert_from_bytes_check!("B62qoqiAgERjCjXhofXiD7cMLJSKD8hE8ZtMh4jX5MPNgKB4CFxxm1N");
+        assert_from_bytes_check!("B62qkiT4kgCawkSEF84ga5kP9QnhmTJEYzcfgGuk6okAJtSBfVcjm1M");
+
+        let pk = PubKey::from_address("B62qnzbXmRNo9q32n4SNu2mpB8e7FYYLH8NmaX6oFCBYjjQ8SbD7uzV")
+            .expect("failed to create pubkey");
+        let mut compressed_bytes = pk.to_compressed_bytes();
+        compressed_bytes[32] = 0x02;
+        assert_eq!(PubKey::from_bytes(&compressed_bytes), Err(Error::Parity));
+        assert_eq!(PubKey::from_bytes(&pk.to_bytes()[..63]), Err(Error::Length));
+    }
 
     #[test]
     fn from_address() {
@@ -149,4 +388,127 @@ mod tests {
         assert_from_address_check!("B62qoqiAgERjCjXhofXiD7cMLJSKD8hE8ZtMh4jX5MPNgKB4CFxxm1N");
         assert_from_address_check!("B62qkiT4kgCawkSEF84ga5kP9QnhmTJEYzcfgGuk6okAJtSBfVcjm1M");
     }
+
+    #[test]
+    fn compressed_from_address() {
+        macro_rules! assert_compressed_from_address_check {
+            ($address:expr) => {
+                let compressed =
+                    CompressedPubKey::from_address($address).expect("failed to create pubkey");
+                assert_eq!(compressed.to_address(), $address);
+
+                let pk = compressed
+                    .decompress()
+                    .expect("failed to decompress pubkey");
+                assert_eq!(
+                    pk,
+                    PubKey::from_address($address).expect("failed to create pubkey")
+                );
+                assert_eq!(pk.to_compressed(), compressed);
+            };
+        }
+
+        assert_compressed_from_address_check!(
+            "B62qnzbXmRNo9q32n4SNu2mpB8e7FYYLH8NmaX6oFCBYjjQ8SbD7uzV"
+        );
+        assert_compressed_from_address_check!(
+            "B62qicipYxyEHu7QjUqS7QvBipTs5CzgkYZZZkPoKVYBu6tnDUcE9Zt"
+        );
+        assert_compressed_from_address_check!(
+            "B62qoG5Yk4iVxpyczUrBNpwtx2xunhL48dydN53A2VjoRwF8NUTbVr4"
+        );
+
+        assert_eq!(
+            CompressedPubKey::from_address(
+                "B62qnzbXmRNo9q32n4SNu2mpB8e7FYYLH8NmaX6oFCBYjjQ8SbD7uz"
+            ),
+            Err(Error::Length)
+        );
+    }
+
+    #[test]
+    fn decompress() {
+        // Zero is not the x-coordinate of any point on the curve
+        let compressed = CompressedPubKey {
+            x: BaseField::from(0u64),
+            is_odd: false,
+        };
+        assert_eq!(compressed.decompress(), Err(Error::XCoordinate));
+        assert_eq!(
+            PubKey::from_address(&compressed.to_address()),
+            Err(Error::XCoordinate)
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        let address = "B62qnzbXmRNo9q32n4SNu2mpB8e7FYYLH8NmaX6oFCBYjjQ8SbD7uzV";
+        let json = format!("\"{}\"", address);
+
+        let pk = PubKey::from_address(address).expect("failed to create pubkey");
+        assert_eq!(
+            serde_json::to_string(&pk).expect("failed to serialize"),
+            json
+        );
+        assert_eq!(
+            serde_json::from_str::<PubKey>(&json).expect("failed to deserialize"),
+            pk
+        );
+
+        let compressed = pk.to_compressed();
+        assert_eq!(
+            serde_json::to_string(&compressed).expect("failed to serialize"),
+            json
+        );
+        assert_eq!(
+            serde_json::from_str::<CompressedPubKey>(&json).expect("failed to deserialize"),
+            compressed
+        );
+
+        assert!(serde_json::from_str::<PubKey>(
+            "\"B62qnzbXmRNo9q32n4SNu2mpB8e7FYYLH8NmaX6oFCBYjjQ8SbD7uz\""
+        )
+        .is_err());
+        assert!(serde_json::from_str::<PubKey>(&pk.to_hex()).is_err());
+    }
+
+    #[test]
+    fn compressed_map_key() {
+        use std::collections::{BTreeSet, HashMap};
+
+        let addresses = [
+            "B62qnzbXmRNo9q32n4SNu2mpB8e7FYYLH8NmaX6oFCBYjjQ8SbD7uzV",
+            "B62qicipYxyEHu7QjUqS7QvBipTs5CzgkYZZZkPoKVYBu6tnDUcE9Zt",
+            "B62qrKG4Z8hnzZqp1AL8WsQhQYah3quN1qUj3SyfJA8Lw135qWWg1mi",
+        ];
+
+        let mut balances = HashMap::new();
+        for (i, address) in addresses.iter().enumerate() {
+            let compressed =
+                CompressedPubKey::from_address(address).expect("failed to create pubkey");
+            balances.insert(compressed, i);
+        }
+        for (i, address) in addresses.iter().enumerate() {
+            let pk = PubKey::from_address(address).expect("failed to create pubkey");
+            assert_eq!(balances.get(&pk.to_compressed()), Some(&i));
+        }
+
+        let ordered: BTreeSet<CompressedPubKey> = balances.keys().copied().collect();
+        assert_eq!(ordered.len(), addresses.len());
+        assert!(ordered
+            .iter()
+            .zip(ordered.iter().skip(1))
+            .all(|(a, b)| (a.x, a.is_odd) < (b.x, b.is_odd)));
+
+        let even = CompressedPubKey {
+            x: BaseField::from(1u64),
+            is_odd: false,
+        };
+        let odd = CompressedPubKey {
+            x: BaseField::from(1u64),
+            is_odd: true,
+        };
+        assert!(even < odd);
+    }
 }
@@ -3,9 +3,10 @@
 //! Definition of random oracle input structure and
 //! methods for serializing into bytes and field elements
 
-use crate::{BaseField, FieldHelpers, ScalarField};
-use ark_ff::PrimeField;
+use crate::{BaseField, Error, FieldHelpers, ScalarField};
+use ark_ff::{BigInteger, Field, PrimeField, Zero};
 use bitvec::{prelude::*, view::AsBits};
+use zeroize::Zeroize;
 
 /// Random oracle input structure
 ///
@@ -44,11 +45,17 @@ use bitvec::{prelude::*, view::AsBits};
 /// of the random oracle input get serialized together in two different ways: both as *bytes* and
 /// as a vector of *field elements*.  The random oracle input encapsulates and automates this
 /// complexity.
+///
+/// **Kimchi:** Since Mina's Berkeley hard fork (see [crate::create_kimchi]) non-field members are
+/// no longer serialized as a bit string, but *packed* as (field element, bit length) pairs that
+/// are combined into as few field elements as possible.  The random oracle input records both
+/// serializations, so the same [crate::Hashable] implementation works for either signer.
 
 #[derive(Default)]
 pub struct ROInput {
     fields: Vec<BaseField>,
     bits: BitVec<Lsb0, u8>,
+    packed: Vec<(BaseField, usize)>,
 }
 
 impl ROInput {
@@ -57,6 +64,7 @@ impl ROInput {
         ROInput {
             fields: vec![],
             bits: BitVec::new(),
+            packed: vec![],
         }
     }
 
@@ -68,29 +76,61 @@ impl ROInput {
     /// Append a scalar field element
     pub fn append_scalar(&mut self, s: ScalarField) {
         // mina scalars are 255 bytes
-        let bytes = s.to_bytes(); // TODO: Combine these two into one-liner
+        let mut bytes = s.to_bytes(); // TODO: Combine these two into one-liner
         let bits = &bytes.as_bits::<Lsb0>()[..ScalarField::size_in_bits()];
         self.bits.extend(bits);
+        self.packed.push((
+            BaseField::from_le_bytes_mod_order(&bytes),
+            ScalarField::size_in_bits(),
+        ));
+        bytes.zeroize();
     }
 
     /// Append a single bit
     pub fn append_bit(&mut self, b: bool) {
         self.bits.push(b);
+        self.packed.push((BaseField::from(b as u64), 1));
     }
 
     /// Append bytes
     pub fn append_bytes(&mut self, bytes: &[u8]) {
         self.bits.extend_from_bitslice(bytes.as_bits::<Lsb0>());
+        self.packed
+            .extend(bytes.iter().map(|b| (BaseField::from(*b as u64), 8)));
     }
 
     /// Append a 32-bit unsigned integer
     pub fn append_u32(&mut self, x: u32) {
-        self.append_bytes(&x.to_le_bytes());
+        self.bits
+            .extend_from_bitslice(x.to_le_bytes().as_bits::<Lsb0>());
+        self.packed.push((BaseField::from(x as u64), 32));
     }
 
     /// Append a 64-bit unsigned integer
     pub fn append_u64(&mut self, x: u64) {
-        self.append_bytes(&x.to_le_bytes());
+        self.bits
+            .extend_from_bitslice(x.to_le_bytes().as_bits::<Lsb0>());
+        self.packed.push((BaseField::from(x), 64));
+    }
+
+    /// Append base field element `x` as a `size_in_bits < 255` bit value
+    ///
+    /// Returns [Error::Length] if `size_in_bits` is too large and [Error::Field] if `x` is not
+    /// less than `2^size_in_bits` (since kimchi packs `x` as is).
+    pub fn append_packed(&mut self, x: BaseField, size_in_bits: usize) -> Result<(), Error> {
+        if size_in_bits >= BaseField::size_in_bits() {
+            return Err(Error::Length);
+        }
+        if x.into_repr().num_bits() as usize > size_in_bits {
+            return Err(Error::Field);
+        }
+
+        let bytes = x.to_bytes();
+        self.bits
+            .extend_from_bitslice(&bytes.as_bits::<Lsb0>()[..size_in_bits]);
+        self.packed.push((x, size_in_bits));
+
+        Ok(())
     }
 
     /// Serialize random oracle input to bytes
@@ -147,6 +187,61 @@ impl ROInput {
 
         fields
     }
+
+    /// Serialize random oracle input to vector of base field elements packed for kimchi
+    ///
+    /// The field elements are followed by the packed values, which are accumulated (most
+    /// significant first) into field elements of less than 255 bits.
+    pub fn to_packed_fields(&self) -> Vec<BaseField> {
+        let mut fields: Vec<BaseField> = self.fields.clone();
+        if self.packed.is_empty() {
+            return fields;
+        }
+
+        let (mut acc, mut acc_size) = (BaseField::zero(), 0);
+        for (x, size_in_bits) in self.packed.iter() {
+            if acc_size + size_in_bits < BaseField::size_in_bits() {
+                acc = acc * BaseField::from(2u64).pow([*size_in_bits as u64]) + x;
+                acc_size += size_in_bits;
+            } else {
+                fields.push(acc);
+                acc = *x;
+                acc_size = *size_in_bits;
+            }
+        }
+        fields.push(acc);
+
+        fields
+    }
+
+    /// Serialize random oracle input packed for kimchi to bytes
+    pub fn to_packed_bytes(&self) -> Vec<u8> {
+        let bits: BitVec<Lsb0, u8> =
+            self.to_packed_fields()
+                .iter()
+                .fold(BitVec::new(), |mut acc, fe| {
+                    acc.extend_from_bitslice(
+                        &fe.to_bytes().as_bits::<Lsb0>()[..BaseField::size_in_bits()],
+                    );
+
+                    acc
+                });
+
+        bits.into()
+    }
+}
+
+impl Zeroize for ROInput {
+    fn zeroize(&mut self) {
+        self.fields.zeroize();
+        self.bits.as_mut_raw_slice().zeroize();
+        self.bits.clear();
+        for (x, size_in_bits) in self.packed.iter_mut() {
+            x.zeroize();
+            size_in_bits.zeroize();
+        }
+        self.packed.clear();
+    }
 }
 
 #[cfg(test)]
@@ -864,4 +959,116 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn zeroize() {
+        let mut roi: ROInput = ROInput::new();
+        roi.append_field(BaseField::from(31u64));
+        roi.append_scalar(ScalarField::from(45u64));
+        roi.append_u32(0xdeadbeef);
+        roi.zeroize();
+
+        assert!(roi.fields.is_empty());
+        assert!(roi.bits.is_empty());
+        assert!(roi.packed.is_empty());
+        assert!(roi.to_bytes().is_empty());
+        assert!(roi.to_fields().is_empty());
+        assert!(roi.to_packed_fields().is_empty());
+    }
+
+    #[test]
+    fn append_packed() {
+        let mut roi: ROInput = ROInput::new();
+        assert_eq!(roi.append_packed(BaseField::from(5u64), 3), Ok(()));
+        assert_eq!(roi.append_packed(BaseField::from(0x1234u64), 16), Ok(()));
+        assert_eq!(roi.append_packed(BaseField::zero(), 0), Ok(()));
+        assert_eq!(roi.bits.len(), 19);
+        assert_eq!(roi.bits.as_raw_slice(), [0xa5, 0x91, 0x00]);
+        assert_eq!(
+            roi.packed,
+            vec![
+                (BaseField::from(5u64), 3),
+                (BaseField::from(0x1234u64), 16),
+                (BaseField::zero(), 0)
+            ]
+        );
+
+        // Values must fit in the given size, which must be less than the field size
+        assert_eq!(
+            roi.append_packed(BaseField::from(8u64), 3),
+            Err(Error::Field)
+        );
+        assert_eq!(
+            roi.append_packed(BaseField::from(1u64), 0),
+            Err(Error::Field)
+        );
+        assert_eq!(
+            roi.append_packed(-BaseField::from(1u64), 254),
+            Err(Error::Field)
+        );
+        assert_eq!(
+            roi.append_packed(BaseField::from(1u64), 255),
+            Err(Error::Length)
+        );
+        assert_eq!(roi.bits.len(), 19);
+        assert_eq!(roi.packed.len(), 3);
+    }
+
+    #[test]
+    fn to_packed_fields() {
+        let mut roi: ROInput = ROInput::new();
+        assert!(roi.to_packed_fields().is_empty());
+
+        // Fields come first and packed values are most significant first
+        roi.append_u32(1);
+        roi.append_field(BaseField::from(31u64));
+        roi.append_bit(true);
+        roi.append_bytes(&[0x01, 0x02]);
+        assert_eq!(
+            roi.to_packed_fields(),
+            vec![
+                BaseField::from(31u64),
+                BaseField::from((((1u64 << 1) + 1) << 16) + 0x0102)
+            ]
+        );
+
+        // Packed values are split into field elements of less than 255 bits
+        let mut roi: ROInput = ROInput::new();
+        roi.append_u64(1);
+        roi.append_u64(2);
+        roi.append_u64(3);
+        roi.append_u64(4);
+        assert_eq!(
+            roi.to_packed_fields(),
+            vec![
+                BaseField::from_decimal("340282366920938463500268095579187314691")
+                    .expect("failed to create field"),
+                BaseField::from(4u64)
+            ]
+        );
+        // as opposed to the legacy bit string serialization (in 254-bit chunks)
+        assert_eq!(
+            roi.to_fields(),
+            vec![
+                BaseField::from_decimal(
+                    "25108406941546723056364004793593481054836439088298861789185"
+                )
+                .expect("failed to create field"),
+                BaseField::zero()
+            ]
+        );
+    }
+
+    #[test]
+    fn to_packed_bytes() {
+        let mut roi: ROInput = ROInput::new();
+        roi.append_field(BaseField::from(2u64));
+        roi.append_u32(3);
+        // Each field element is serialized as 255 bits
+        let mut bytes = vec![0; 64];
+        bytes[0] = 0x02;
+        bytes[31] = 0x80;
+        bytes[32] = 0x01;
+        assert_eq!(roi.to_packed_bytes(), bytes);
+    }
 }
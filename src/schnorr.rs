@@ -3,8 +3,12 @@
 //! An implementation of the singer interface for the Mina signature algorithm
 //!
 //! Details: <https://github.com/MinaProtocol/mina/blob/develop/docs/specs/signatures/description.md>
+//!
+//! The secret dependent parts of signing are side-channel hardened, see [crate::ct] for the
+//! exact guarantees.
 
 use ark_ec::{
+    msm::VariableBaseMSM,
     AffineCurve,     // for prime_subgroup_generator()
     ProjectiveCurve, // for into_affine()
 };
@@ -18,84 +22,249 @@ use blake2::{
     digest::{Update, VariableOutput},
     VarBlake2b,
 };
-use oracle::{
-    poseidon::{SpongeConstants, SpongeState},
-    rndoracle::{ArithmeticSponge, Sponge},
-};
+use oracle::{poseidon::SpongeConstants, rndoracle::ArithmeticSponge};
+use rand::{rngs::OsRng, CryptoRng, Rng, RngCore};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use std::ops::Neg;
+use subtle::Choice;
+use zeroize::{Zeroize, Zeroizing};
 
 use crate::{
-    BaseField, CurvePoint, FieldHelpers, Hashable, Keypair, NetworkId, PubKey, ROInput,
-    ScalarField, Signable, Signature, Signer,
+    ct, hash::Hasher, pubkey::decompress, BaseField, CurvePoint, Error, FieldHelpers, Hashable,
+    Keypair, NetworkId, PubKey, ROInput, ScalarField, Signable, Signature, Signer, VerifyError,
 };
 
 /// Schnorr signer context for the Mina signature algorithm
 ///
 /// For details about the signature algorithm please see [crate::schnorr]
+#[derive(Clone)]
 pub struct Schnorr<SC: SpongeConstants> {
-    sponge: ArithmeticSponge<BaseField, SC>,
+    hasher: Hasher<SC>,
     network_id: NetworkId,
 }
 
 impl<SC: SpongeConstants> Signer for Schnorr<SC> {
-    fn sign<S>(&mut self, kp: Keypair, input: S) -> Signature
+    fn sign<S>(&mut self, kp: &Keypair, input: S) -> Result<Signature, Error>
     where
         S: Signable,
     {
-        let k: ScalarField = self.blinding_hash(&kp, input);
-        let r: CurvePoint = CurvePoint::prime_subgroup_generator().mul(k).into_affine();
-        let k: ScalarField = if r.y.into_repr().is_even() { k } else { -k };
+        self.sign_hedged(kp, input, None)
+    }
 
-        let e: ScalarField = self.message_hash(&kp.public, r.x, input);
-        let s: ScalarField = k + e * kp.secret.to_scalar();
+    fn sign_with_rng<S>(
+        &mut self,
+        kp: &Keypair,
+        input: S,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<Signature, Error>
+    where
+        S: Signable,
+    {
+        let mut aux = [0; 32];
+        rng.fill_bytes(&mut aux);
+        let sig = self.sign_hedged(kp, input, Some(&aux));
+        aux.zeroize();
 
-        Signature::new(r.x, s)
+        sig
+    }
+
+    #[cfg(feature = "parallel")]
+    fn sign_many<S>(&mut self, kp: &Keypair, inputs: &[S]) -> Result<Vec<Signature>, Error>
+    where
+        S: Signable + Sync,
+        Self: Clone + Sync,
+    {
+        // Each worker signs with its own copy of the context (and thus sponge)
+        let ctx: &Self = self;
+        inputs
+            .par_iter()
+            .map_init(
+                || ctx.clone(),
+                |ctx, input| ctx.sign_hedged(kp, *input, None),
+            )
+            .collect()
     }
 
     fn verify<S>(&mut self, sig: Signature, public: PubKey, input: S) -> bool
     where
         S: Signable,
     {
-        let ev: ScalarField = self.message_hash(&public, sig.rx, input);
+        self.verify_signature(sig, public, input).is_ok()
+    }
 
-        let sv: CurvePoint = CurvePoint::prime_subgroup_generator()
-            .mul(sig.s)
-            .into_affine();
-        // Perform addition and infinity check in projective coordinates for performance
-        let rv = public.to_point().mul(ev).neg().add_mixed(&sv);
-        if rv.is_zero() {
-            return false;
+    fn verify_detailed<S>(
+        &mut self,
+        sig: Signature,
+        public: PubKey,
+        input: S,
+    ) -> Result<(), VerifyError>
+    where
+        S: Signable,
+    {
+        let result = self.verify_signature(sig, public, input);
+        if result.is_err() && result != Err(VerifyError::InvalidPublicKey) {
+            // Check whether the signature is for another (standard) network
+            for other_network_id in [NetworkId::MAINNET, NetworkId::TESTNET].iter() {
+                if *other_network_id == self.network_id {
+                    continue;
+                }
+
+                let network_id = std::mem::replace(&mut self.network_id, other_network_id.clone());
+                let other_result = self.verify_signature(sig, public, input);
+                self.network_id = network_id;
+
+                if other_result.is_ok() {
+                    return Err(VerifyError::WrongNetwork(other_network_id.clone()));
+                }
+            }
         }
-        let rv = rv.into_affine();
 
-        rv.y.into_repr().is_even() && rv.x == sig.rx
+        result
+    }
+
+    fn verify_batch<S>(&mut self, batch: &[(Signature, PubKey, S)]) -> Vec<bool>
+    where
+        S: Signable,
+    {
+        // Each signature is valid iff s*G - e*P = R, where R is the point with x-coordinate
+        // rx and even y-coordinate, so the batch is checked with a single multi-scalar
+        // multiplication of a random linear combination of these equations
+        let mut items = vec![];
+        let mut results = vec![false; batch.len()];
+        for (i, (sig, public, input)) in batch.iter().enumerate() {
+            // Items with invalid public keys or rx are rejected up front
+            if public.validate().is_err() {
+                continue;
+            }
+            let r = match decompress(sig.rx, false) {
+                Some(r) => r,
+                None => continue,
+            };
+            if let Ok(e) = self.message_hash(public, sig.rx, *input) {
+                items.push(BatchItem {
+                    index: i,
+                    s: sig.s,
+                    e,
+                    public: public.to_point(),
+                    r,
+                });
+            }
+        }
+
+        verify_batch_items(&items, &mut results);
+
+        results
     }
 }
 
 impl<SC: SpongeConstants> Schnorr<SC> {
     /// Create a new Schnorr signer context for network instance `network_id` using arithmetic sponge defined by `sponge`.
     pub fn new(sponge: ArithmeticSponge<BaseField, SC>, network_id: NetworkId) -> Schnorr<SC> {
-        Schnorr::<SC> { sponge, network_id }
+        Schnorr::<SC> {
+            hasher: Hasher::new(sponge),
+            network_id,
+        }
+    }
+
+    /// Create a new kimchi Schnorr signer context for network instance `network_id` using arithmetic sponge defined by `sponge`.
+    ///
+    /// Kimchi signers hash the packed random oracle input (see [ROInput::to_packed_fields]), as
+    /// Mina does since the Berkeley hard fork.
+    pub fn new_kimchi(
+        sponge: ArithmeticSponge<BaseField, SC>,
+        network_id: NetworkId,
+    ) -> Schnorr<SC> {
+        Schnorr::<SC> {
+            hasher: Hasher::new_kimchi(sponge),
+            network_id,
+        }
+    }
+
+    /// Set the maximum number of domain prefixes whose sponge state is cached by the signer's
+    /// hasher (see [Hasher::set_prefix_cache_capacity])
+    pub fn set_prefix_cache_capacity(&mut self, capacity: usize) {
+        self.hasher.set_prefix_cache_capacity(capacity);
+    }
+
+    // Sign `input`, additionally mixing the auxiliary randomness `aux` (if any) into the nonce
+    fn sign_hedged<S>(
+        &mut self,
+        kp: &Keypair,
+        input: S,
+        aux: Option<&[u8]>,
+    ) -> Result<Signature, Error>
+    where
+        S: Signable,
+    {
+        // N.B. The nonce is secret (it reveals the secret key given the signature), so it is
+        //      handled in constant time (see [crate::ct]) and zeroized after use
+        let k: Zeroizing<ScalarField> = Zeroizing::new(self.blinding_hash(kp, input, aux)?);
+        let r: CurvePoint = ct::mul_generator(&k);
+        let k: Zeroizing<ScalarField> = Zeroizing::new(ct::conditional_negate(
+            &k,
+            Choice::from(r.y.into_repr().is_odd() as u8),
+        ));
+
+        let e: ScalarField = self.message_hash(&kp.public, r.x, input)?;
+        let s: ScalarField = *k + e * *kp.secret.to_scalar();
+
+        Ok(Signature::new(r.x, s))
     }
 
-    fn domain_bytes<S>(network_id: NetworkId) -> Vec<u8>
+    fn verify_signature<S>(
+        &mut self,
+        sig: Signature,
+        public: PubKey,
+        input: S,
+    ) -> Result<(), VerifyError>
     where
         S: Signable,
     {
-        let mut domain_string = S::domain_string(network_id);
-        // Domain prefixes have a max length of 20 and are padded with '*'
-        assert!(domain_string.len() <= 20);
-        domain_string = &domain_string[..std::cmp::min(domain_string.len(), 20)];
-        let mut bytes = format!("{:*<20}", domain_string).as_bytes().to_vec();
-        bytes.resize(32, 0);
+        // Reject invalid public keys up front
+        if public.validate().is_err() {
+            return Err(VerifyError::InvalidPublicKey);
+        }
+
+        let ev: ScalarField = self
+            .message_hash(&public, sig.rx, input)
+            .map_err(VerifyError::MessageHash)?;
+
+        let sv: CurvePoint = ct::mul_generator(&sig.s);
+        // Perform addition and infinity check in projective coordinates for performance
+        let rv = public.to_point().mul(ev).neg().add_mixed(&sv);
+        if rv.is_zero() {
+            return Err(VerifyError::PointAtInfinity);
+        }
+        let rv = rv.into_affine();
+
+        if !rv.y.into_repr().is_even() {
+            return Err(VerifyError::OddY);
+        }
+        if rv.x != sig.rx {
+            return Err(VerifyError::RxMismatch);
+        }
 
-        bytes
+        Ok(())
     }
 
     // This function uses a cryptographic hash function to create a uniformly and
     // randomly distributed nonce.  It is crucial for security that no two different
     // messages share the same nonce.
-    fn blinding_hash<H>(&self, kp: &Keypair, input: H) -> ScalarField
+    //
+    // N.B. The random oracle input and the bytes hashed here contain the secret key, so
+    //      they are zeroized after use.
+    //
+    // When auxiliary randomness `aux` is given it is hashed after the input (hedged
+    // signing), so that the nonce stays secret even if the randomness is bad, while a
+    // fault injected into the computation no longer yields two signatures on the same
+    // message sharing a nonce.  Without it the nonce is deterministic.
+    fn blinding_hash<H>(
+        &self,
+        kp: &Keypair,
+        input: H,
+        aux: Option<&[u8]>,
+    ) -> Result<ScalarField, Error>
     where
         H: Hashable,
     {
@@ -104,10 +273,36 @@ impl<SC: SpongeConstants> Schnorr<SC> {
         let mut roi: ROInput = input.to_roinput();
         roi.append_field(kp.public.to_point().x);
         roi.append_field(kp.public.to_point().y);
-        roi.append_scalar(kp.secret.to_scalar());
-        roi.append_bytes(&[self.network_id.into()]);
+        let mut roi_bytes = if self.hasher.is_kimchi() {
+            // Kimchi packs the network id (as a little-endian integer, which must fit in a
+            // single packed value) and hashes the secret key as a base field element
+            //   N.B. Packed values are serialized after all field elements, so the order in
+            //   which they are appended here does not matter
+            let network_id = self.network_id.to_bytes();
+            if 8 * network_id.len() >= BaseField::size_in_bits() {
+                return Err(Error::NetworkId);
+            }
+            roi.append_packed(
+                BaseField::from_le_bytes_mod_order(&network_id),
+                8 * network_id.len(),
+            )?;
+            let mut secret_bytes = kp.secret.to_scalar().to_bytes();
+            roi.append_field(BaseField::from_le_bytes_mod_order(&secret_bytes));
+            secret_bytes.zeroize();
 
-        hasher.update(roi.to_bytes());
+            roi.to_packed_bytes()
+        } else {
+            roi.append_scalar(*kp.secret.to_scalar());
+            roi.append_bytes(&self.network_id.to_bytes());
+
+            roi.to_bytes()
+        };
+        hasher.update(&roi_bytes);
+        roi_bytes.zeroize();
+        roi.zeroize();
+        if let Some(aux) = aux {
+            hasher.update(aux);
+        }
 
         let mut bytes = [0; 32];
         hasher.finalize_variable(|out| bytes.copy_from_slice(out));
@@ -117,15 +312,23 @@ impl<SC: SpongeConstants> Schnorr<SC> {
         //   simple by avoiding reduction modulo p.
         bytes[bytes.len() - 1] &= 0b0011_1111;
 
-        ScalarField::from_random_bytes(&bytes[..]).expect("failed to create scalar from bytes")
+        let k = ScalarField::from_random_bytes(&bytes[..]).ok_or(Error::Field);
+        bytes.zeroize();
+
+        k
     }
 
     // This function uses a cryptographic hash function (based on a sponge construction) to
     // convert the message to be signed (and some other information) into a uniformly and
     // randomly distributed scalar field element.  It uses Mina's variant of the Poseidon
-    // SNARK-friendly cryptographic hash function.
+    // SNARK-friendly cryptographic hash function (see [crate::hash]).
     // Details: <https://github.com/o1-labs/cryptography-rfcs/blob/httpsnapps-notary-signatures/mina/001-poseidon-sponge.md>
-    fn message_hash<S>(&mut self, pub_key: &PubKey, rx: BaseField, input: S) -> ScalarField
+    fn message_hash<S>(
+        &mut self,
+        pub_key: &PubKey,
+        rx: BaseField,
+        input: S,
+    ) -> Result<ScalarField, Error>
     where
         S: Signable,
     {
@@ -134,23 +337,273 @@ impl<SC: SpongeConstants> Schnorr<SC> {
         roi.append_field(pub_key.to_point().y);
         roi.append_field(rx);
 
-        // Set sponge initial state (explicitly init state so signer context can be reused)
-        // N.B. Mina sets the sponge's initial state by hashing the input's domain bytes
-        self.sponge.state = vec![BaseField::zero(); self.sponge.state.len()];
-        self.sponge.sponge_state = SpongeState::Absorbed(0);
-        self.sponge
-            .absorb(&[
-                BaseField::from_bytes(&Schnorr::<SC>::domain_bytes::<S>(self.network_id))
-                    .expect("invalid domain bytes"),
-            ]);
-        self.sponge.squeeze();
-
-        // Absorb random oracle input
-        self.sponge.absorb(&roi.to_fields());
-
-        // Squeeze and convert from base field element to scalar field element
+        // N.B. Mina sets the sponge's initial state by hashing the input's domain prefix, whose
+        //      resulting state the hasher caches (i.e. per input type and network)
+        let fields = self.hasher.to_fields(&roi);
+        let e = self
+            .hasher
+            .hash_with_prefix(&S::domain_string(&self.network_id), &fields)?;
+
+        // Convert from base field element to scalar field element
         // Since the difference in modulus between the two fields is < 2^125, w.h.p., a
         // random value from one field will fit in the other field.
-        ScalarField::from_repr(self.sponge.squeeze().into_repr()).expect("failed to create scalar")
+        ScalarField::from_repr(e.into_repr()).ok_or(Error::Field)
+    }
+}
+
+// Batch verification item (see [Signer::verify_batch])
+struct BatchItem {
+    index: usize,
+    s: ScalarField,
+    e: ScalarField,
+    public: CurvePoint,
+    r: CurvePoint,
+}
+
+// Verify batch `items`, recording which are valid in `results`
+//
+// When the batch fails, its halves are verified recursively in order to identify the
+// invalid items.
+fn verify_batch_items(items: &[BatchItem], results: &mut [bool]) {
+    if items.is_empty() {
+        return;
+    }
+
+    // Random nonzero 128-bit linear combination coefficients
+    let z: Vec<ScalarField> = items
+        .iter()
+        .map(|_| ScalarField::from(OsRng.gen::<u128>() | 1))
+        .collect();
+
+    let mut bases = vec![CurvePoint::prime_subgroup_generator()];
+    let mut scalars = vec![items
+        .iter()
+        .zip(&z)
+        .fold(ScalarField::zero(), |acc, (item, z)| acc + *z * item.s)];
+    for (item, z) in items.iter().zip(&z) {
+        bases.push(item.public);
+        scalars.push(-(*z * item.e));
+        bases.push(item.r);
+        scalars.push(-*z);
+    }
+    let scalars: Vec<_> = scalars.iter().map(|scalar| scalar.into_repr()).collect();
+
+    if VariableBaseMSM::multi_scalar_mul(&bases, &scalars).is_zero() {
+        for item in items {
+            results[item.index] = true;
+        }
+    } else if items.len() > 1 {
+        let (left, right) = items.split_at(items.len() / 2);
+        verify_batch_items(left, results);
+        verify_batch_items(right, results);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oracle::{
+        pasta,
+        poseidon::{PlonkSpongeConstants15W, PlonkSpongeConstantsBasic, Sponge},
+    };
+    use std::borrow::Cow;
+
+    #[derive(Clone, Copy)]
+    struct Message(u64);
+
+    impl Hashable for Message {
+        fn to_roinput(self) -> ROInput {
+            let mut roi = ROInput::new();
+            roi.append_u64(self.0);
+
+            roi
+        }
+    }
+
+    impl Signable for Message {
+        fn domain_string(network_id: &NetworkId) -> Cow<'static, str> {
+            match network_id {
+                NetworkId::MAINNET => "MessageSigMainnet".into(),
+                NetworkId::TESTNET => "MessageSigTestnet".into(),
+                NetworkId::Custom(name) => format!("MessageSig{}", name).into(),
+            }
+        }
+    }
+
+    fn create(network_id: NetworkId) -> Schnorr<PlonkSpongeConstantsBasic> {
+        Schnorr::new(ArithmeticSponge::new(pasta::fp::params()), network_id)
+    }
+
+    // Create signature with field component `rx` whose recomputed R is `k*G`
+    fn forge(
+        ctx: &mut Schnorr<PlonkSpongeConstantsBasic>,
+        kp: &Keypair,
+        rx: BaseField,
+        k: ScalarField,
+        msg: Message,
+    ) -> Signature {
+        let e = ctx
+            .message_hash(&kp.public, rx, msg)
+            .expect("failed to hash message");
+
+        Signature::new(rx, k + e * *kp.secret.to_scalar())
+    }
+
+    #[test]
+    fn verify_detailed() {
+        let kp = Keypair::rand(&mut OsRng);
+        let msg = Message(1729);
+        let mut ctx = create(NetworkId::TESTNET);
+
+        let sig = ctx.sign(&kp, msg).expect("failed to sign");
+        assert_eq!(ctx.verify_detailed(sig, kp.public, msg), Ok(()));
+        assert!(ctx.verify(sig, kp.public, msg));
+
+        // Signature for the other network
+        let mut mainnet_ctx = create(NetworkId::MAINNET);
+        assert_eq!(
+            mainnet_ctx.verify_detailed(sig, kp.public, msg),
+            Err(VerifyError::WrongNetwork(NetworkId::TESTNET))
+        );
+        assert!(!mainnet_ctx.verify(sig, kp.public, msg));
+        let sig = mainnet_ctx.sign(&kp, msg).expect("failed to sign");
+        assert_eq!(
+            ctx.verify_detailed(sig, kp.public, msg),
+            Err(VerifyError::WrongNetwork(NetworkId::MAINNET))
+        );
+        // Network is restored
+        assert_eq!(mainnet_ctx.verify_detailed(sig, kp.public, msg), Ok(()));
+
+        // Invalid public key
+        assert_eq!(
+            ctx.verify_detailed(sig, PubKey::new(CurvePoint::zero()), msg),
+            Err(VerifyError::InvalidPublicKey)
+        );
+
+        // Point at infinity
+        let sig = forge(
+            &mut ctx,
+            &kp,
+            BaseField::from(1u64),
+            ScalarField::zero(),
+            msg,
+        );
+        assert_eq!(
+            ctx.verify_detailed(sig, kp.public, msg),
+            Err(VerifyError::PointAtInfinity)
+        );
+
+        // Odd and even y-coordinates
+        let k = ScalarField::from(42u64);
+        let r = CurvePoint::prime_subgroup_generator().mul(k).into_affine();
+        let (k_even, k_odd) = if r.y.into_repr().is_even() {
+            (k, -k)
+        } else {
+            (-k, k)
+        };
+        let sig = forge(&mut ctx, &kp, r.x, k_odd, msg);
+        assert_eq!(
+            ctx.verify_detailed(sig, kp.public, msg),
+            Err(VerifyError::OddY)
+        );
+        let sig = forge(&mut ctx, &kp, r.x, k_even, msg);
+        assert_eq!(ctx.verify_detailed(sig, kp.public, msg), Ok(()));
+
+        // X-coordinate mismatch
+        let sig = forge(&mut ctx, &kp, r.x + BaseField::from(1u64), k_even, msg);
+        assert_eq!(
+            ctx.verify_detailed(sig, kp.public, msg),
+            Err(VerifyError::RxMismatch)
+        );
+        assert!(!ctx.verify(sig, kp.public, msg));
+    }
+
+    #[test]
+    fn sign_with_rng() {
+        let kp = Keypair::rand(&mut OsRng);
+        let msg = Message(1729);
+        let mut ctx = create(NetworkId::TESTNET);
+
+        // Default signing is deterministic
+        let sig = ctx.sign(&kp, msg).expect("failed to sign");
+        assert_eq!(ctx.sign(&kp, msg), Ok(sig));
+
+        // Hedged signatures use fresh nonces and verify as usual
+        let hedged = ctx
+            .sign_with_rng(&kp, msg, &mut OsRng)
+            .expect("failed to sign");
+        let other = ctx
+            .sign_with_rng(&kp, msg, &mut OsRng)
+            .expect("failed to sign");
+        assert_ne!(hedged.rx, sig.rx);
+        assert_ne!(hedged.rx, other.rx);
+        assert!(ctx.verify(hedged, kp.public, msg));
+        assert!(ctx.verify(other, kp.public, msg));
+        assert!(!ctx.verify(hedged, kp.public, Message(1730)));
+    }
+
+    #[test]
+    fn domain_errors() {
+        let kp = Keypair::rand(&mut OsRng);
+        let msg = Message(1729);
+
+        // Domain string "MessageSigberkeley" is valid
+        let mut ctx = create(NetworkId::custom("berkeley").expect("invalid network"));
+        let sig = ctx.sign(&kp, msg).expect("failed to sign");
+        assert!(ctx.verify(sig, kp.public, msg));
+
+        // Domain string is too long
+        let mut ctx = create(NetworkId::custom("berkeley-private").expect("invalid network"));
+        assert_eq!(ctx.sign(&kp, msg), Err(Error::Domain));
+        #[cfg(feature = "parallel")]
+        assert_eq!(ctx.sign_many(&kp, &[msg, msg]), Err(Error::Domain));
+        assert!(!ctx.verify(sig, kp.public, msg));
+        assert_eq!(
+            ctx.verify_detailed(sig, kp.public, msg),
+            Err(VerifyError::MessageHash(Error::Domain))
+        );
+        assert_eq!(ctx.verify_batch(&[(sig, kp.public, msg)]), vec![false]);
+    }
+
+    #[test]
+    fn kimchi() {
+        let kp = Keypair::rand(&mut OsRng);
+        let msg = Message(1729);
+        let mut ctx = create(NetworkId::TESTNET);
+        let mut kimchi_ctx = Schnorr::<PlonkSpongeConstants15W>::new_kimchi(
+            ArithmeticSponge::new(pasta::fp_3::params()),
+            NetworkId::TESTNET,
+        );
+
+        let sig = kimchi_ctx.sign(&kp, msg).expect("failed to sign");
+        assert!(kimchi_ctx.verify(sig, kp.public, msg));
+        assert!(!kimchi_ctx.verify(sig, kp.public, Message(1730)));
+        assert!(!ctx.verify(sig, kp.public, msg));
+        let legacy_sig = ctx.sign(&kp, msg).expect("failed to sign");
+        assert!(!kimchi_ctx.verify(legacy_sig, kp.public, msg));
+
+        // Same sponge, but legacy random oracle input serialization, which for a single u64
+        // coincides with the packed one, except in the nonce derivation
+        let mut legacy_ctx = Schnorr::<PlonkSpongeConstants15W>::new(
+            ArithmeticSponge::new(pasta::fp_3::params()),
+            NetworkId::TESTNET,
+        );
+        assert_ne!(legacy_ctx.sign(&kp, msg), Ok(sig));
+        assert!(legacy_ctx.verify(sig, kp.public, msg));
+
+        // Kimchi packs the network id into a single field element, so custom network names
+        // are limited to 31 bytes
+        let network_id = NetworkId::custom(&"n".repeat(32)).expect("invalid network");
+        let mut kimchi_ctx = Schnorr::<PlonkSpongeConstants15W>::new_kimchi(
+            ArithmeticSponge::new(pasta::fp_3::params()),
+            network_id.clone(),
+        );
+        assert_eq!(kimchi_ctx.sign(&kp, msg), Err(Error::NetworkId));
+        // Legacy nonce derivation accepts it (but its message domain string is too long)
+        let mut legacy_ctx = Schnorr::<PlonkSpongeConstantsBasic>::new(
+            ArithmeticSponge::new(pasta::fp::params()),
+            network_id,
+        );
+        assert_eq!(legacy_ctx.sign(&kp, msg), Err(Error::Domain));
     }
 }
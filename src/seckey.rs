@@ -1,9 +1,20 @@
 //! Secret key structures and helpers
 
-use crate::ScalarField;
+use bs58;
+use sha2::{Digest, Sha256};
+use subtle::{Choice, ConstantTimeEq};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::{Error, FieldHelpers, ScalarField};
+
+/// Length of Mina secret keys
+pub const MINA_SEC_KEY_LEN: usize = 52;
 
 /// Secret key
-#[derive(Clone, Copy, PartialEq, Eq)] // No Debug nor Display
+///
+/// Secret keys are not `Copy` and their scalar is zeroized when dropped.  Comparison of
+/// secret keys is constant-time.
+#[derive(Clone)] // No Copy, Debug nor Display
 pub struct SecKey(ScalarField);
 
 impl SecKey {
@@ -12,8 +23,142 @@ impl SecKey {
         Self(scalar)
     }
 
+    /// Deserialize Mina base58 encoded secret key (e.g. "EK...")
+    pub fn from_base58(base58: &str) -> Result<Self, Error> {
+        if base58.len() != MINA_SEC_KEY_LEN {
+            return Err(Error::Length);
+        }
+
+        let mut bytes = bs58::decode(base58).into_vec().map_err(|_| Error::Base58)?;
+
+        let (raw, checksum) = (&bytes[..bytes.len() - 4], &bytes[bytes.len() - 4..]);
+        let hash = Sha256::digest(&Sha256::digest(raw)[..]);
+        if checksum != &hash[..4] {
+            return Err(Error::Checksum);
+        }
+
+        let (version, scalar_bytes) = (&raw[..2], &raw[2..]);
+        if version != [0x5a, 0x01] {
+            return Err(Error::Version);
+        }
+
+        let scalar = ScalarField::from_bytes(scalar_bytes);
+        bytes.zeroize();
+
+        Ok(SecKey::new(scalar?))
+    }
+
     /// Convert secret key into scalar field element
-    pub fn to_scalar(self) -> ScalarField {
-        self.0
+    ///
+    /// The returned copy of the scalar is zeroized when dropped.
+    pub fn to_scalar(&self) -> Zeroizing<ScalarField> {
+        Zeroizing::new(self.0)
+    }
+
+    /// Serialize secret key into Mina base58 encoding (e.g. "EK...")
+    pub fn to_base58(&self) -> String {
+        // N.B. Allocated with its final capacity, so that no unwiped copies of the secret
+        //      key bytes are left behind when extending
+        let mut raw: Vec<u8> = Vec::with_capacity(2 + 32 + 4);
+        raw.extend([
+            0x5a, // version for base58 check
+            0x01, // private key version
+        ]);
+
+        // secret key scalar (little-endian)
+        let mut scalar_bytes = self.0.to_bytes();
+        raw.extend(&scalar_bytes);
+        scalar_bytes.zeroize();
+
+        // 4-byte checksum
+        let hash = Sha256::digest(&Sha256::digest(&raw[..])[..]);
+        raw.extend(&hash[..4]);
+
+        let base58 = bs58::encode(&raw).into_string();
+        raw.zeroize();
+
+        base58
+    }
+}
+
+impl ConstantTimeEq for SecKey {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        (self.0).0 .0.ct_eq(&(other.0).0 .0)
+    }
+}
+
+impl PartialEq for SecKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl Eq for SecKey {}
+
+impl Drop for SecKey {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_base58() {
+        assert_eq!(SecKey::from_base58("").map(|_| ()), Err(Error::Length));
+        assert_eq!(
+            SecKey::from_base58("EKDt66ubGg5SDiwcQABWfFZaruq6idcyrLLfyZQjoH4CN3PHEiN0").map(|_| ()),
+            Err(Error::Base58)
+        );
+        assert_eq!(
+            SecKey::from_base58("EKDt66ubGg5SDiwcQABWfFZaruq6idcyrLLfyZQjoH4CN3PHEiNk").map(|_| ()),
+            Err(Error::Checksum)
+        );
+        assert_eq!(
+            SecKey::from_base58("B62qnzbXmRNo9q32n4SNu2mpB8e7FYYLH8NmaX6oFCBYjjQ8SbD7").map(|_| ()),
+            Err(Error::Checksum)
+        );
+        assert_eq!(
+            SecKey::from_base58("EKFpqH93JXW7SrmC74m8fCtBdpwiSHKpj7yk5oZRhGiCkY2KrD2G").map(|_| ()),
+            Err(Error::Version)
+        );
+        assert_eq!(
+            SecKey::from_base58("EKDt66ubGg5SDiwcQABWfFZaruq6idcyrLLfyZQjoH4CNRrNUSFM").map(|_| ()),
+            Err(Error::Field)
+        );
+
+        SecKey::from_base58("EKDt66ubGg5SDiwcQABWfFZaruq6idcyrLLfyZQjoH4CN3PHEiNj")
+            .expect("failed to decode secret key");
+    }
+
+    #[test]
+    fn to_base58() {
+        macro_rules! assert_base58_check {
+            ($base58:expr) => {
+                let sec_key = SecKey::from_base58($base58).expect("failed to create secret key");
+                assert_eq!(sec_key.to_base58(), $base58);
+            };
+        }
+
+        assert_base58_check!("EKDt66ubGg5SDiwcQABWfFZaruq6idcyrLLfyZQjoH4CN3PHEiNj");
+        assert_base58_check!("EKE3W1XLtaAUXSKcpeh6J7ntqeeDrkjih3DVGM5Z3Hdb2wB2dtR2");
+        assert_base58_check!("EKEaQv91GMtM91VsHrmh8wsdarZpNpPSpGCr8BCzyhbEwsrMQQWo");
+        assert_base58_check!("EKEDGUnFazxGZFkVq5Ng2oUTZ7Vau7tqYMPCPzmW6BtKJBs3oWcL");
+        assert_base58_check!("EKFKgDtU3rcuFTVSEpmpXSkukjmX4cKefYREi6Sdsk7E7wsT7KRw");
+    }
+
+    #[test]
+    fn ct_eq() {
+        let sec_key = SecKey::from_base58("EKDt66ubGg5SDiwcQABWfFZaruq6idcyrLLfyZQjoH4CN3PHEiNj")
+            .expect("failed to create secret key");
+        let other = SecKey::from_base58("EKE3W1XLtaAUXSKcpeh6J7ntqeeDrkjih3DVGM5Z3Hdb2wB2dtR2")
+            .expect("failed to create secret key");
+
+        assert!(bool::from(sec_key.ct_eq(&sec_key.clone())));
+        assert!(!bool::from(sec_key.ct_eq(&other)));
+        assert!(sec_key == sec_key.clone());
+        assert!(sec_key != other);
     }
 }
@@ -0,0 +1,258 @@
+//! Mina secret box structures and algorithms
+//!
+//! Definition of the secret box format used by the Mina daemon and tooling to store
+//! encrypted secret keys, i.e. argon2i password hashing and xsalsa20poly1305
+//! authenticated encryption with base58 check encoded nonce, salt and ciphertext.
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use bs58;
+use crypto_secretbox::{
+    aead::{Aead, KeyInit},
+    Nonce, XSalsa20Poly1305,
+};
+use rand::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+use crate::Error;
+
+/// Secret box authenticated encryption primitive
+pub const BOX_PRIMITIVE: &str = "xsalsa20poly1305";
+
+/// Secret box password hashing primitive
+pub const PW_PRIMITIVE: &str = "argon2i";
+
+/// Default password hashing difficulty (memory limit in bytes, operations limit)
+///
+/// These are libsodium's moderate argon2i limits, which is what Mina uses.
+pub const PW_DIFF: (u32, u32) = (134217728, 6);
+
+const NONCE_LEN: usize = 24;
+const SALT_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+
+/// Mina secret box
+///
+/// Serializes to and from the JSON format of Mina's secret key files, for example
+///
+/// ```json
+/// {
+///   "box_primitive": "xsalsa20poly1305",
+///   "pw_primitive": "argon2i",
+///   "nonce": "6zErzh9TCQK3UDuyiyLB75UpCtzarHacK7wr2aX",
+///   "pwsalt": "AiXWDwM94Vic3uecHxA3BavS2eTU",
+///   "pwdiff": [134217728, 6],
+///   "ciphertext": "CEHTZuW8EsfGzLL5gy82xb8NsTbzbCBPUoFU4fBMK3gLYSuEQ1oy6ArAcVHhWwcoifBN2hceg"
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SecretBox {
+    /// Authenticated encryption primitive
+    pub box_primitive: String,
+    /// Password hashing primitive
+    pub pw_primitive: String,
+    /// Base58 check encoded nonce
+    pub nonce: String,
+    /// Base58 check encoded password salt
+    pub pwsalt: String,
+    /// Password hashing difficulty (memory limit in bytes, operations limit)
+    pub pwdiff: (u32, u32),
+    /// Base58 check encoded ciphertext
+    pub ciphertext: String,
+}
+
+impl SecretBox {
+    /// Encrypt `plaintext` with `password` using password hashing difficulty `pwdiff`
+    /// (see [PW_DIFF]) and randomness from `rng` for the nonce and salt
+    pub fn encrypt(
+        plaintext: &[u8],
+        password: &[u8],
+        pwdiff: (u32, u32),
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<Self, Error> {
+        let mut nonce = [0u8; NONCE_LEN];
+        let mut pwsalt = [0u8; SALT_LEN];
+        rng.fill_bytes(&mut nonce);
+        rng.fill_bytes(&mut pwsalt);
+
+        let key = derive_key(password, &pwsalt, pwdiff)?;
+        let ciphertext = XSalsa20Poly1305::new_from_slice(&key[..])
+            .map_err(|_| Error::Encryption)?
+            .encrypt(&Nonce::from(nonce), plaintext)
+            .map_err(|_| Error::Encryption)?;
+
+        Ok(SecretBox {
+            box_primitive: BOX_PRIMITIVE.to_string(),
+            pw_primitive: PW_PRIMITIVE.to_string(),
+            nonce: to_base58_check(&nonce),
+            pwsalt: to_base58_check(&pwsalt),
+            pwdiff,
+            ciphertext: to_base58_check(&ciphertext),
+        })
+    }
+
+    /// Decrypt secret box with `password` and return the plaintext (which is zeroized when
+    /// dropped)
+    pub fn decrypt(&self, password: &[u8]) -> Result<Zeroizing<Vec<u8>>, Error> {
+        if self.box_primitive != BOX_PRIMITIVE {
+            return Err(Error::Primitive);
+        }
+        if self.pw_primitive != PW_PRIMITIVE {
+            return Err(Error::Primitive);
+        }
+
+        let nonce_bytes = from_base58_check(&self.nonce)?;
+        if nonce_bytes.len() != NONCE_LEN {
+            return Err(Error::Length);
+        }
+        let mut nonce = Nonce::default();
+        nonce.copy_from_slice(&nonce_bytes);
+
+        let pwsalt = from_base58_check(&self.pwsalt)?;
+        if pwsalt.len() != SALT_LEN {
+            return Err(Error::Length);
+        }
+        let ciphertext = from_base58_check(&self.ciphertext)?;
+
+        let key = derive_key(password, &pwsalt, self.pwdiff)?;
+        XSalsa20Poly1305::new_from_slice(&key[..])
+            .map_err(|_| Error::Decryption)?
+            .decrypt(&nonce, &ciphertext[..])
+            .map(Zeroizing::new)
+            .map_err(|_| Error::Decryption)
+    }
+
+    /// Deserialize secret box from JSON
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        serde_json::from_str(json).map_err(|_| Error::Json)
+    }
+
+    /// Serialize secret box into JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("failed to serialize secret box")
+    }
+}
+
+// Password hashing with argon2i, where the memory limit is given in bytes (like libsodium)
+fn derive_key(
+    password: &[u8],
+    pwsalt: &[u8],
+    pwdiff: (u32, u32),
+) -> Result<Zeroizing<[u8; KEY_LEN]>, Error> {
+    let (mem_limit, ops_limit) = pwdiff;
+    let params = Params::new(mem_limit / 1024, ops_limit, 1, Some(KEY_LEN))
+        .map_err(|_| Error::PasswordDifficulty)?;
+
+    let mut key = Zeroizing::new([0u8; KEY_LEN]);
+    Argon2::new(Algorithm::Argon2i, Version::V0x13, params)
+        .hash_password_into(password, pwsalt, &mut key[..])
+        .map_err(|_| Error::PasswordHash)?;
+
+    Ok(key)
+}
+
+fn to_base58_check(bytes: &[u8]) -> String {
+    let mut raw: Vec<u8> = vec![
+        0x02, // version for base58 check
+    ];
+
+    raw.extend(bytes);
+
+    // 4-byte checksum
+    let hash = Sha256::digest(&Sha256::digest(&raw[..])[..]);
+    raw.extend(&hash[..4]);
+
+    bs58::encode(raw).into_string()
+}
+
+fn from_base58_check(base58: &str) -> Result<Vec<u8>, Error> {
+    let bytes = bs58::decode(base58).into_vec().map_err(|_| Error::Base58)?;
+    if bytes.len() < 5 {
+        return Err(Error::Base58);
+    }
+
+    let (raw, checksum) = (&bytes[..bytes.len() - 4], &bytes[bytes.len() - 4..]);
+    let hash = Sha256::digest(&Sha256::digest(raw)[..]);
+    if checksum != &hash[..4] {
+        return Err(Error::Checksum);
+    }
+
+    if raw[0] != 0x02 {
+        return Err(Error::Version);
+    }
+
+    Ok(raw[1..].to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Produced with libsodium's crypto_pwhash (argon2i13) and crypto_secretbox_easy,
+    // i.e. the same primitives the Mina OCaml tooling binds to
+    const TEST_BOX: &str = r#"{"box_primitive":"xsalsa20poly1305","pw_primitive":"argon2i","nonce":"6Hv82rZkpGuFZCxsZe9Vadmoo5NTWmQjZXkkNvR","pwsalt":"8z9HbGTMMsSimyQSsGgFxR9uv6PV","pwdiff":[8388608,3],"ciphertext":"BV87QKAyJqQM6GirnMVLx1iUzDHsoPKmreZES94EDV1ibM3znWNckoV1FRniBi6Y95HaRTzfu"}"#;
+
+    #[test]
+    fn json() {
+        let secret_box = SecretBox::from_json(TEST_BOX).expect("failed to parse secret box");
+        assert_eq!(secret_box.pwdiff, (8388608, 3));
+        assert_eq!(secret_box.to_json(), TEST_BOX);
+
+        assert_eq!(SecretBox::from_json(""), Err(Error::Json));
+        assert_eq!(
+            SecretBox::from_json(r#"{"box_primitive":"xsalsa20poly1305"}"#),
+            Err(Error::Json)
+        );
+    }
+
+    #[test]
+    fn decrypt() {
+        let secret_box = SecretBox::from_json(TEST_BOX).expect("failed to parse secret box");
+        let mut plaintext = secret_box.decrypt(b"").expect("failed to decrypt");
+        plaintext[1..].reverse();
+        assert_eq!(
+            hex::encode(&*plaintext),
+            "013ca187a58f09da346844964310c7e0dd948a9105702b716f4d732e042e0c172e"
+        );
+
+        assert_eq!(secret_box.decrypt(b"wrong"), Err(Error::Decryption));
+
+        let mut bad_box = secret_box.clone();
+        bad_box.pw_primitive = "argon2id".to_string();
+        assert_eq!(bad_box.decrypt(b""), Err(Error::Primitive));
+
+        let mut bad_box = secret_box.clone();
+        bad_box.nonce = "6Hv82rZkpGuFZCxsZe9Vadmoo5NTWmQjZXkkNvS".to_string();
+        assert_eq!(bad_box.decrypt(b""), Err(Error::Checksum));
+
+        let mut bad_box = secret_box;
+        bad_box.pwsalt = bad_box.nonce.clone();
+        assert_eq!(bad_box.decrypt(b""), Err(Error::Length));
+    }
+
+    #[test]
+    fn encrypt_decrypt() {
+        let plaintext = b"the quick brown fox";
+        let secret_box = SecretBox::encrypt(
+            plaintext,
+            b"jumped over",
+            (8388608, 3),
+            &mut rand::rngs::OsRng,
+        )
+        .expect("failed to encrypt");
+
+        assert_eq!(secret_box.box_primitive, BOX_PRIMITIVE);
+        assert_eq!(secret_box.pw_primitive, PW_PRIMITIVE);
+        assert_eq!(
+            *secret_box
+                .decrypt(b"jumped over")
+                .expect("failed to decrypt"),
+            plaintext
+        );
+        assert_eq!(
+            SecretBox::from_json(&secret_box.to_json()).expect("failed to parse secret box"),
+            secret_box
+        );
+    }
+}
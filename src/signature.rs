@@ -1,8 +1,15 @@
 //! Mina signature structure and associated helpers
 
+use sha2::{Digest, Sha256};
 use std::fmt;
 
-use crate::{BaseField, FieldHelpers, ScalarField};
+#[cfg(feature = "serde")]
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{BaseField, Error, FieldHelpers, NetworkId, ScalarField};
+
+/// Length of signature bytes (see [Signature::to_bytes])
+pub const SIGNATURE_BYTES_LEN: usize = 64;
 
 /// Signature structure
 #[derive(Clone, Copy, Eq, fmt::Debug, PartialEq)]
@@ -19,15 +26,435 @@ impl Signature {
     pub fn new(rx: BaseField, s: ScalarField) -> Self {
         Self { rx, s }
     }
-}
 
-impl fmt::Display for Signature {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    /// Deserialize signature from bytes (see [Signature::to_bytes]), checking that
+    /// both components are canonical field elements
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() != SIGNATURE_BYTES_LEN {
+            return Err(Error::Length);
+        }
+
+        let (mut rx_bytes, mut s_bytes) = (bytes[..32].to_vec(), bytes[32..].to_vec());
+        rx_bytes.reverse();
+        s_bytes.reverse();
+
+        Signature::from_components(
+            BaseField::from_bytes(&rx_bytes),
+            ScalarField::from_bytes(&s_bytes),
+        )
+    }
+
+    /// Deserialize signature from hex (see [Signature::to_hex])
+    pub fn from_hex(hex: &str) -> Result<Self, Error> {
+        let bytes = hex::decode(hex).map_err(|_| Error::Hex)?;
+
+        Signature::from_bytes(&bytes)
+    }
+
+    /// Serialize signature into bytes, i.e. the big-endian field component followed
+    /// by the big-endian scalar component
+    pub fn to_bytes(self) -> [u8; SIGNATURE_BYTES_LEN] {
         let mut rx_bytes = self.rx.to_bytes();
         let mut s_bytes = self.s.to_bytes();
         rx_bytes.reverse();
         s_bytes.reverse();
 
-        write!(f, "{}{}", hex::encode(rx_bytes), hex::encode(s_bytes))
+        let mut bytes = [0u8; SIGNATURE_BYTES_LEN];
+        bytes[..32].copy_from_slice(&rx_bytes);
+        bytes[32..].copy_from_slice(&s_bytes);
+
+        bytes
+    }
+
+    /// Serialize signature into hex (see [Signature::to_bytes])
+    pub fn to_hex(self) -> String {
+        hex::encode(self.to_bytes())
+    }
+
+    /// Deserialize signature from the decimal strings of its `field` and `scalar`
+    /// components, checking that both are canonical field elements
+    pub fn from_decimals(field: &str, scalar: &str) -> Result<Self, Error> {
+        Signature::from_components(
+            BaseField::from_decimal(field),
+            ScalarField::from_decimal(scalar),
+        )
+    }
+
+    /// Deserialize signature from Mina JSON (see [Signature::to_json])
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let json: SignatureJson = serde_json::from_str(json).map_err(|_| Error::Json)?;
+
+        Signature::from_decimals(&json.field, &json.scalar)
+    }
+
+    /// Serialize signature into Mina JSON, i.e. `{"field":"<decimal>","scalar":"<decimal>"}`,
+    /// as used by the Mina GraphQL API and the JS mina-signer package
+    #[cfg(feature = "serde")]
+    pub fn to_json(self) -> String {
+        serde_json::to_string(&SignatureJson::from(self)).expect("Failed to serialize signature")
+    }
+
+    /// Deserialize Mina base58 encoded signature (e.g. "7mX...")
+    pub fn from_base58(base58: &str) -> Result<Self, Error> {
+        let bytes = bs58::decode(base58).into_vec().map_err(|_| Error::Base58)?;
+        if bytes.len() != 2 + SIGNATURE_BYTES_LEN + 4 {
+            return Err(Error::Length);
+        }
+
+        let (raw, checksum) = (&bytes[..bytes.len() - 4], &bytes[bytes.len() - 4..]);
+        let hash = Sha256::digest(&Sha256::digest(raw)[..]);
+        if checksum != &hash[..4] {
+            return Err(Error::Checksum);
+        }
+
+        let (version, rx_bytes, s_bytes) = (&raw[..2], &raw[2..34], &raw[34..]);
+        if version != [0x9a, 0x01] {
+            return Err(Error::Version);
+        }
+
+        Signature::from_components(
+            BaseField::from_bytes(rx_bytes),
+            ScalarField::from_bytes(s_bytes),
+        )
+    }
+
+    // Combine deserialized signature components, reporting which of them is invalid
+    fn from_components(
+        rx: Result<BaseField, Error>,
+        s: Result<ScalarField, Error>,
+    ) -> Result<Self, Error> {
+        let component_err = |err, component_err| match err {
+            Error::Field => component_err,
+            err => err,
+        };
+
+        Ok(Signature::new(
+            rx.map_err(|err| component_err(err, Error::SignatureField))?,
+            s.map_err(|err| component_err(err, Error::SignatureScalar))?,
+        ))
+    }
+
+    /// Serialize signature into Mina base58 encoding (e.g. "7mX..."), as used by the
+    /// Mina daemon, client and GraphQL API
+    pub fn to_base58(self) -> String {
+        let mut raw: Vec<u8> = vec![
+            0x9a, // version for base58 check
+            0x01, // signature version
+        ];
+
+        // signature components (little-endian)
+        raw.extend(self.rx.to_bytes());
+        raw.extend(self.s.to_bytes());
+
+        // 4-byte checksum
+        let hash = Sha256::digest(&Sha256::digest(&raw[..])[..]);
+        raw.extend(&hash[..4]);
+
+        bs58::encode(raw).into_string()
+    }
+}
+
+impl fmt::Display for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_hex())
+    }
+}
+
+/// Signature verification error (see [crate::Signer::verify_detailed])
+///
+/// Verification recomputes `R = s*G - e*P` from the signature `(rx, s)`, public key `P`
+/// and message hash `e`, and checks that `R` has even y-coordinate and x-coordinate `rx`.
+#[derive(Clone, fmt::Debug, PartialEq, Eq)]
+pub enum VerifyError {
+    /// Public key is not a valid curve point (see [crate::PubKey::validate])
+    InvalidPublicKey,
+
+    /// Recomputed `R` is the point at infinity
+    PointAtInfinity,
+
+    /// Recomputed `R` has odd y-coordinate
+    OddY,
+
+    /// Recomputed `R` has x-coordinate different from the signature's `rx`
+    RxMismatch,
+
+    /// Signature is invalid, but would be valid on network `NetworkId`
+    WrongNetwork(NetworkId),
+
+    /// Failed to hash the signed input (see [Error])
+    MessageHash(Error),
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::InvalidPublicKey => write!(f, "Invalid public key"),
+            VerifyError::PointAtInfinity => write!(f, "Invalid signature point at infinity"),
+            VerifyError::OddY => write!(f, "Invalid signature odd y-coordinate"),
+            VerifyError::RxMismatch => write!(f, "Invalid signature x-coordinate mismatch"),
+            VerifyError::WrongNetwork(network_id) => {
+                write!(f, "Invalid signature, but valid on network {}", network_id)
+            }
+            VerifyError::MessageHash(err) => write!(f, "Failed to hash signed input: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+impl From<VerifyError> for Error {
+    fn from(err: VerifyError) -> Self {
+        match err {
+            VerifyError::MessageHash(err) => err,
+            VerifyError::InvalidPublicKey => Error::PublicKey,
+            _ => Error::Signature,
+        }
+    }
+}
+
+// Mina JSON signature representation (see [Signature::to_json])
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+struct SignatureJson {
+    field: String,
+    scalar: String,
+}
+
+#[cfg(feature = "serde")]
+impl From<Signature> for SignatureJson {
+    fn from(sig: Signature) -> Self {
+        SignatureJson {
+            field: sig.rx.to_decimal(),
+            scalar: sig.s.to_decimal(),
+        }
+    }
+}
+
+/// Signatures serialize in the Mina JSON form (see [Signature::to_json])
+#[cfg(feature = "serde")]
+impl Serialize for Signature {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SignatureJson::from(*self).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Signature {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let json = SignatureJson::deserialize(deserializer)?;
+        Signature::from_decimals(&json.field, &json.scalar).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_hex() {
+        macro_rules! assert_from_hex_check {
+            ($sig_hex:expr) => {
+                let sig = Signature::from_hex($sig_hex).expect("failed to decode signature");
+                assert_eq!(sig.to_hex(), $sig_hex);
+                assert_eq!(sig.to_string(), $sig_hex);
+                assert_eq!(Signature::from_bytes(&sig.to_bytes()), Ok(sig));
+            };
+        }
+
+        assert_from_hex_check!("11a36a8dfe5b857b95a2a7b7b17c62c3ea33411ae6f4eb3a907064aecae353c60794f1d0288322fe3f8bb69d6fabd4fd7c15f8d09f8783b2f087a80407e299af");
+        assert_from_hex_check!("124c592178ed380cdffb11a9f8e1521bf940e39c13f37ba4c55bb4454ea69fba3c3595a55b06dac86261bb8ab97126bf3f7fff70270300cb97ff41401a5ef789");
+        assert_from_hex_check!("00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000");
+
+        assert_eq!(Signature::from_hex(""), Err(Error::Length));
+        assert_eq!(
+            Signature::from_hex("11a36a8dfe5b857b95a2a7b7b17c62c3ea33411ae6f4eb3a907064aecae353c60794f1d0288322fe3f8bb69d6fabd4fd7c15f8d09f8783b2f087a80407e299"),
+            Err(Error::Length)
+        );
+        assert_eq!(
+            Signature::from_hex("g1a36a8dfe5b857b95a2a7b7b17c62c3ea33411ae6f4eb3a907064aecae353c60794f1d0288322fe3f8bb69d6fabd4fd7c15f8d09f8783b2f087a80407e299af"),
+            Err(Error::Hex)
+        );
+
+        // Field modulus is not a canonical field element
+        assert_eq!(
+            Signature::from_hex("40000000000000000000000000000000224698fc094cf91b992d30ed000000010794f1d0288322fe3f8bb69d6fabd4fd7c15f8d09f8783b2f087a80407e299af"),
+            Err(Error::SignatureField)
+        );
+        // Scalar modulus is not a canonical scalar
+        assert_eq!(
+            Signature::from_hex("11a36a8dfe5b857b95a2a7b7b17c62c3ea33411ae6f4eb3a907064aecae353c640000000000000000000000000000000224698fc0994a8dd8c46eb2100000001"),
+            Err(Error::SignatureScalar)
+        );
+    }
+
+    #[test]
+    fn from_base58() {
+        macro_rules! assert_from_base58_eq {
+            ($sig_base58:expr, $sig_hex:expr) => {
+                let sig = Signature::from_base58($sig_base58).expect("failed to decode signature");
+                assert_eq!(sig.to_hex(), $sig_hex);
+                assert_eq!(sig.to_base58(), $sig_base58);
+            };
+        }
+
+        assert_from_base58_eq!(
+            "7mXQYfqpcs1uDRVcpddkKazQHfPXPJfJxC9rn2WwQ6tAARBnGK1pjiKYegk7Jwu8nq8a2wrRuMvy4agJWobBi9Ei5dr7d7tp",
+            "11a36a8dfe5b857b95a2a7b7b17c62c3ea33411ae6f4eb3a907064aecae353c60794f1d0288322fe3f8bb69d6fabd4fd7c15f8d09f8783b2f087a80407e299af"
+        );
+        assert_from_base58_eq!(
+            "7mXP1roWQrg65zZWhB97mzh2U4DHMsbRxTXvAMqQBuGL9eFgun7RRWyfYgYCKuDj1b8fgKUPYhCtUxvSDEMxwZkASPX3dCqw",
+            "124c592178ed380cdffb11a9f8e1521bf940e39c13f37ba4c55bb4454ea69fba3c3595a55b06dac86261bb8ab97126bf3f7fff70270300cb97ff41401a5ef789"
+        );
+
+        assert_eq!(Signature::from_base58(""), Err(Error::Length));
+        assert_eq!(
+            Signature::from_base58("7mXQYfqpcs1uDRVcpddkKazQHfPXPJfJxC9rn2WwQ6tAARBnGK1pjiKYegk7Jwu8nq8a2wrRuMvy4agJWobBi9Ei5dr7d7t0"),
+            Err(Error::Base58)
+        );
+        assert_eq!(
+            Signature::from_base58("7mXQYfqpcs1uDRVcpddkKazQHfPXPJfJxC9rn2WwQ6tAARBnGK1pjiKYegk7Jwu8nq8a2wrRuMvy4agJWobBi9Ei5dr7d7tq"),
+            Err(Error::Checksum)
+        );
+        assert_eq!(
+            Signature::from_base58("WsXCvnSVBHkyTq7K7EhCD32MnYSR7Sx6EcE6hjh96xxoSZkanaWdhXNrhLRGrDK7PHSQob7Av7PCSoeUJYdtGbMWKTCb3fazf"),
+            Err(Error::Length)
+        );
+        assert_eq!(
+            Signature::from_base58("7mXz3DEF2u4HfF4QwYpNXz5xk9NHX7xqREn8EWgqxY7i3SPASBm9CtCxJKLuoA7bDUkWB18VnQU5DMLTgjPSciWEzUg8qsK8"),
+            Err(Error::Version)
+        );
+        assert_eq!(
+            Signature::from_base58("7p5EXCoahsYyBNFBMNXb3XXfB9vKt61nKnE5gNEvZUgsMhRdrJDLfMDvkEMHprsmasjXbQmZCZE7mh6nLFkykyo9K9NRaLca"),
+            Err(Error::Version)
+        );
+        // Scalar modulus is not a canonical scalar
+        assert_eq!(
+            Signature::from_base58("7mXQYfqpcs1uDRVcpddkKazQHfPXPJfJxC9rn2WwQ6tAARATNMs2Pnpy2YEshdQHLjsVF87SUygq6Ls34eD3bGYjbBuavyk2"),
+            Err(Error::SignatureScalar)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn from_json() {
+        macro_rules! assert_from_json_eq {
+            ($sig_json:expr, $sig_hex:expr) => {
+                let sig = Signature::from_json($sig_json).expect("failed to decode signature");
+                assert_eq!(sig.to_hex(), $sig_hex);
+                assert_eq!(sig.to_json(), $sig_json);
+            };
+        }
+
+        assert_from_json_eq!(
+            r#"{"field":"7978049910726616927075298742385001574587620942310654323357397558995139646406","scalar":"3429352238474987065427486162608449491113877901219474382951875744532516739503"}"#,
+            "11a36a8dfe5b857b95a2a7b7b17c62c3ea33411ae6f4eb3a907064aecae353c60794f1d0288322fe3f8bb69d6fabd4fd7c15f8d09f8783b2f087a80407e299af"
+        );
+        assert_from_json_eq!(
+            r#"{"field":"42","scalar":"28948022309329048855892746252171976963363056481941647379679742748393362948096"}"#,
+            "000000000000000000000000000000000000000000000000000000000000002a40000000000000000000000000000000224698fc0994a8dd8c46eb2100000000"
+        );
+
+        // Whitespace and field order are irrelevant
+        assert_eq!(
+            Signature::from_json(r#"{ "scalar": "2", "field": "1" }"#),
+            Ok(Signature::new(
+                BaseField::from(1u64),
+                ScalarField::from(2u64)
+            ))
+        );
+
+        assert_eq!(Signature::from_json(""), Err(Error::Json));
+        assert_eq!(Signature::from_json(r#"{"field":"42"}"#), Err(Error::Json));
+        assert_eq!(
+            Signature::from_json(r#"{"field":42,"scalar":"1"}"#),
+            Err(Error::Json)
+        );
+        assert_eq!(
+            Signature::from_json(r#"{"field":"0x2a","scalar":"1"}"#),
+            Err(Error::Decimal)
+        );
+        assert_eq!(
+            Signature::from_json(
+                r#"{"field":"42","scalar":"28948022309329048855892746252171976963363056481941647379679742748393362948097"}"#
+            ),
+            Err(Error::SignatureScalar)
+        );
+        assert_eq!(
+            Signature::from_json(
+                r#"{"field":"28948022309329048855892746252171976963363056481941560715954676764349967630337","scalar":"1"}"#
+            ),
+            Err(Error::SignatureField)
+        );
+    }
+
+    #[test]
+    fn to_bytes() {
+        let sig = Signature::new(BaseField::from(1u64), ScalarField::from(2u64));
+        let bytes = sig.to_bytes();
+
+        assert_eq!(bytes[31], 1);
+        assert_eq!(bytes[63], 2);
+        assert_eq!(bytes.iter().map(|b| *b as u32).sum::<u32>(), 3);
+        assert_eq!(
+            sig.to_hex(),
+            "00000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000000000000000000000000000000000002"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        let sig = Signature::new(BaseField::from(42u64), -ScalarField::from(1u64));
+        let json = r#"{"field":"42","scalar":"28948022309329048855892746252171976963363056481941647379679742748393362948096"}"#;
+
+        assert_eq!(
+            serde_json::to_string(&sig).expect("failed to serialize"),
+            json
+        );
+        assert_eq!(
+            serde_json::from_str::<Signature>(json).expect("failed to deserialize"),
+            sig
+        );
+
+        assert!(serde_json::from_str::<Signature>(r#"{"field":"42"}"#).is_err());
+        assert!(serde_json::from_str::<Signature>(r#"{"field":"42","scalar":"-1"}"#).is_err());
+        assert!(serde_json::from_str::<Signature>(
+            r#"{"field":"28948022309329048855892746252171976963363056481941560715954676764349967630337","scalar":"1"}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn verify_error_display() {
+        assert_eq!(
+            VerifyError::WrongNetwork(NetworkId::MAINNET).to_string(),
+            "Invalid signature, but valid on network mainnet"
+        );
+        assert_eq!(
+            VerifyError::WrongNetwork(NetworkId::custom("zeko").expect("invalid network"))
+                .to_string(),
+            "Invalid signature, but valid on network zeko"
+        );
+        assert_eq!(
+            VerifyError::MessageHash(Error::Domain).to_string(),
+            "Failed to hash signed input: Invalid domain string"
+        );
+    }
+
+    #[test]
+    fn verify_error_into_error() {
+        assert_eq!(Error::from(VerifyError::InvalidPublicKey), Error::PublicKey);
+        assert_eq!(Error::from(VerifyError::PointAtInfinity), Error::Signature);
+        assert_eq!(Error::from(VerifyError::OddY), Error::Signature);
+        assert_eq!(Error::from(VerifyError::RxMismatch), Error::Signature);
+        assert_eq!(
+            Error::from(VerifyError::WrongNetwork(NetworkId::TESTNET)),
+            Error::Signature
+        );
+        assert_eq!(
+            Error::from(VerifyError::MessageHash(Error::Domain)),
+            Error::Domain
+        );
     }
 }
@@ -0,0 +1,266 @@
+//! Vanity address generation
+//!
+//! Parallel search for a keypair whose Mina address matches a given prefix and/or
+//! suffix after the fixed `B62q` header.
+//!
+//! Rather than performing a full scalar multiplication per candidate (as
+//! [Keypair::rand] does), each worker starts from a random secret key `s` with
+//! public key `P = s*G` and then walks the sequence `(s + i, P + i*G)` using a single
+//! point addition per candidate.  Candidates are converted to affine coordinates in
+//! batches so that the cost of field inversion is amortized.
+
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{UniformRand, Zero};
+use rand::rngs::OsRng;
+use rayon::prelude::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+use zeroize::Zeroize;
+
+use crate::{
+    ct, pubkey::MINA_ADDRESS_LEN, CurvePoint, Error, Keypair, PubKey, ScalarField, SecKey,
+};
+
+/// Fixed address header shared by all Mina addresses
+pub const ADDRESS_HEADER: &str = "B62q";
+
+/// Number of candidates converted to affine coordinates at once
+const BATCH_SIZE: usize = 1024;
+
+const BASE58_ALPHABET: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Vanity address pattern
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pattern {
+    prefix: String,
+    suffix: String,
+}
+
+impl Pattern {
+    /// Create pattern matching addresses of the form `B62q<prefix>...<suffix>`,
+    /// checking that such addresses exist
+    pub fn new(prefix: &str, suffix: &str) -> Result<Self, Error> {
+        if prefix.len() + suffix.len() > MINA_ADDRESS_LEN - ADDRESS_HEADER.len() {
+            return Err(Error::Length);
+        }
+
+        if !prefix
+            .chars()
+            .chain(suffix.chars())
+            .all(|c| BASE58_ALPHABET.contains(c))
+        {
+            return Err(Error::Base58);
+        }
+
+        let pattern = Pattern {
+            prefix: prefix.to_string(),
+            suffix: suffix.to_string(),
+        };
+
+        if pattern.prefix_probability() <= 0.0 {
+            return Err(Error::VanityPrefix);
+        }
+
+        Ok(pattern)
+    }
+
+    /// Get the prefix
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// Get the suffix
+    pub fn suffix(&self) -> &str {
+        &self.suffix
+    }
+
+    /// Check whether `address` matches the pattern
+    pub fn is_match(&self, address: &str) -> bool {
+        address.len() == MINA_ADDRESS_LEN
+            && address
+                .get(ADDRESS_HEADER.len()..)
+                .is_some_and(|tail| tail.starts_with(&self.prefix))
+            && address.ends_with(&self.suffix)
+    }
+
+    /// Expected number of candidate keypairs to try before finding a match
+    pub fn difficulty(&self) -> f64 {
+        // The suffix is part of the checksum and so each character is uniformly distributed
+        58f64.powi(self.suffix.len() as i32) / self.prefix_probability()
+    }
+
+    // Probability that a random address matches the prefix
+    //
+    // The leading characters of an address are constrained by its version bytes, so
+    // the prefix is matched against the range of possible addresses.  Addresses are
+    // fixed length, so their numerical order is the lexicographic order of their digits.
+    fn prefix_probability(&self) -> f64 {
+        let (min_address, max_address) = address_range();
+        let tail_len = MINA_ADDRESS_LEN - ADDRESS_HEADER.len() - self.prefix.len();
+
+        // Smallest and largest addresses starting with the prefix
+        let lower = format!("{}{}{}", ADDRESS_HEADER, self.prefix, "1".repeat(tail_len));
+        let upper = format!("{}{}{}", ADDRESS_HEADER, self.prefix, "z".repeat(tail_len));
+
+        let lower = base58_value(std::cmp::max(&lower, &min_address));
+        let upper = base58_value(std::cmp::min(&upper, &max_address));
+        if upper < lower {
+            return 0.0;
+        }
+
+        (upper - lower + 1.0) / (base58_value(&max_address) - base58_value(&min_address) + 1.0)
+    }
+}
+
+/// Search for a keypair whose address matches `pattern` using all available cores
+///
+/// N.B. This does not return until a match is found, see [Pattern::difficulty].
+pub fn search(pattern: &Pattern) -> Keypair {
+    let found = AtomicBool::new(false);
+
+    (0..rayon::current_num_threads())
+        .into_par_iter()
+        .find_map_any(|_| search_worker(pattern, &found))
+        .expect("vanity search terminated without a match")
+}
+
+fn search_worker(pattern: &Pattern, found: &AtomicBool) -> Option<Keypair> {
+    let generator = CurvePoint::prime_subgroup_generator().into_projective();
+    let mut secret = ScalarField::rand(&mut OsRng);
+    let mut point = ct::mul_generator(&secret).into_projective();
+
+    let mut batch = Vec::with_capacity(BATCH_SIZE);
+    let mut result = None;
+    while result.is_none() && !found.load(Ordering::Relaxed) {
+        batch.clear();
+        for _ in 0..BATCH_SIZE {
+            batch.push(point);
+            point += &generator;
+        }
+
+        for (i, public) in ProjectiveCurve::batch_normalization_into_affine(&batch)
+            .into_iter()
+            .enumerate()
+        {
+            if public.is_zero() || !pattern.is_match(&PubKey::new(public).to_address()) {
+                continue;
+            }
+
+            if !found.swap(true, Ordering::Relaxed) {
+                result =
+                    Keypair::from_secret(SecKey::new(secret + ScalarField::from(i as u64))).ok();
+            }
+            break;
+        }
+
+        secret += ScalarField::from(BATCH_SIZE as u64);
+    }
+
+    secret.zeroize();
+    result
+}
+
+// Smallest and largest possible addresses
+fn address_range() -> (String, String) {
+    let version = [0xcb, 0x01, 0x01];
+    let tail_len = 32 + 1 + 4; // x-coordinate, parity and checksum
+    let min_bytes = [&version[..], &[0x00; 37][..tail_len]].concat();
+    let max_bytes = [&version[..], &[0xff; 37][..tail_len]].concat();
+
+    (
+        bs58::encode(min_bytes).into_string(),
+        bs58::encode(max_bytes).into_string(),
+    )
+}
+
+// Approximate numerical value of base58 `digits`
+fn base58_value(digits: &str) -> f64 {
+    digits.chars().fold(0.0, |acc, c| {
+        acc * 58.0 + BASE58_ALPHABET.find(c).unwrap_or(0) as f64
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn address_range() {
+        let (min_address, max_address) = super::address_range();
+        assert_eq!(
+            min_address,
+            "B62qiTKpEPjGTSHZrtM8uXiKgn8So916pLmNJKDhKeyBQL9TDWVsr7H"
+        );
+        assert_eq!(
+            max_address,
+            "B62qs2xPPkdScMRYNu7gyvc7rschELewEcVXjwMFn14ft5rjYi33FFC"
+        );
+    }
+
+    #[test]
+    fn new() {
+        assert!(Pattern::new("", "").is_ok());
+        assert!(Pattern::new("mina", "").is_ok());
+        assert!(Pattern::new("", "Mina").is_ok());
+        assert!(Pattern::new("iTKq", "").is_ok());
+        assert!(Pattern::new("s2xN", "").is_ok());
+
+        assert_eq!(Pattern::new("mina0", ""), Err(Error::Base58));
+        assert_eq!(Pattern::new("", "MinaI"), Err(Error::Base58));
+        assert_eq!(
+            Pattern::new(&"m".repeat(40), &"m".repeat(12)),
+            Err(Error::Length)
+        );
+        assert_eq!(Pattern::new("Mina", ""), Err(Error::VanityPrefix));
+        assert_eq!(Pattern::new("z", ""), Err(Error::VanityPrefix));
+        assert_eq!(Pattern::new("iTKo", ""), Err(Error::VanityPrefix));
+        assert_eq!(Pattern::new("s2xQ", ""), Err(Error::VanityPrefix));
+    }
+
+    fn pattern(prefix: &str, suffix: &str) -> Pattern {
+        Pattern::new(prefix, suffix).expect("failed to create pattern")
+    }
+
+    #[test]
+    fn is_match() {
+        let address = "B62qnzbXmRNo9q32n4SNu2mpB8e7FYYLH8NmaX6oFCBYjjQ8SbD7uzV";
+
+        assert!(pattern("", "").is_match(address));
+        assert!(pattern("nzb", "").is_match(address));
+        assert!(pattern("", "7uzV").is_match(address));
+        assert!(pattern("nz", "uzV").is_match(address));
+        assert!(!pattern("nzc", "").is_match(address));
+        assert!(!pattern("", "7uzv").is_match(address));
+        assert!(!pattern("", "B62q").is_match("B62q"));
+
+        // Non-ASCII input of address length
+        let non_ascii = format!("B62\u{e9}{}", "a".repeat(MINA_ADDRESS_LEN - 5));
+        assert_eq!(non_ascii.len(), MINA_ADDRESS_LEN);
+        assert!(!pattern("", "").is_match(&non_ascii));
+        assert!(!pattern("m", "").is_match(&non_ascii));
+    }
+
+    #[test]
+    fn difficulty() {
+        assert!((pattern("", "").difficulty() - 1.0).abs() < 1e-9);
+        assert!((pattern("", "ab").difficulty() - 3364.0).abs() < 1e-6);
+
+        // Only ten first prefix characters i-s are possible, eight of which fully
+        let difficulty = pattern("m", "").difficulty();
+        assert!(difficulty > 8.0 && difficulty < 9.0);
+        let difficulty = pattern("mm", "a").difficulty();
+        assert!(difficulty > 8.0 * 58.0 * 58.0 && difficulty < 9.0 * 58.0 * 58.0);
+
+        // The first and last characters are only partially possible
+        assert!(pattern("i", "").difficulty() > pattern("m", "").difficulty());
+        assert!(pattern("s", "").difficulty() > pattern("m", "").difficulty());
+    }
+
+    #[test]
+    fn search() {
+        let pattern = pattern("m", "a");
+        let kp = super::search(&pattern);
+
+        assert!(pattern.is_match(&kp.get_address()));
+        assert_eq!(kp.validate(), Ok(()));
+    }
+}
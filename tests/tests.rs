@@ -1,8 +1,16 @@
 pub mod transaction;
 
-use ark_ff::Zero;
-use mina_signer::{BaseField, Keypair, NetworkId, PubKey, ScalarField, Signer};
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{PrimeField, Zero};
+use mina_signer::{
+    hash::{self, Hasher},
+    BaseField, CurvePoint, Error, Hashable, Keypair, NetworkId, PubKey, ROInput, ScalarField,
+    Signable, Signature, Signer, VerifyError,
+};
+use num_bigint::BigUint;
+use oracle::poseidon::SpongeConstants;
 use rand;
+use std::borrow::Cow;
 pub use transaction::Transaction;
 
 enum TransactionType {
@@ -37,10 +45,10 @@ macro_rules! assert_sign_verify_tx {
         tx = tx.set_valid_until($valid_until).set_memo_str($memo);
 
         let mut testnet_ctx = mina_signer::create(NetworkId::TESTNET);
-        let testnet_sig = testnet_ctx.sign(kp, tx);
+        let testnet_sig = testnet_ctx.sign(&kp, tx).expect("failed to sign");
 
         let mut mainnet_ctx = mina_signer::create(NetworkId::MAINNET);
-        let mainnet_sig = mainnet_ctx.sign(kp, tx);
+        let mainnet_sig = mainnet_ctx.sign(&kp, tx).expect("failed to sign");
 
         // Signing checks
         assert_ne!(testnet_sig, mainnet_sig); // Testnet and mainnet sigs are not equal
@@ -53,6 +61,14 @@ macro_rules! assert_sign_verify_tx {
 
         assert_eq!(mainnet_ctx.verify(testnet_sig, kp.public, tx), false);
         assert_eq!(testnet_ctx.verify(mainnet_sig, kp.public, tx), false);
+        assert_eq!(
+            mainnet_ctx.verify_detailed(testnet_sig, kp.public, tx),
+            Err(VerifyError::WrongNetwork(NetworkId::TESTNET))
+        );
+        assert_eq!(
+            testnet_ctx.verify_detailed(mainnet_sig, kp.public, tx),
+            Err(VerifyError::WrongNetwork(NetworkId::MAINNET))
+        );
 
         tx.valid_until = !tx.valid_until;
         assert_eq!(testnet_ctx.verify(testnet_sig, kp.public, tx), false);
@@ -86,7 +102,7 @@ fn signer_test_raw() {
     );
 
     let mut ctx = mina_signer::create(NetworkId::TESTNET);
-    let sig = ctx.sign(kp, tx);
+    let sig = ctx.sign(&kp, tx).expect("failed to sign");
 
     assert_eq!(sig.to_string(),
                 "11a36a8dfe5b857b95a2a7b7b17c62c3ea33411ae6f4eb3a907064aecae353c60794f1d0288322fe3f8bb69d6fabd4fd7c15f8d09f8783b2f087a80407e299af");
@@ -106,7 +122,7 @@ fn signer_zero_test() {
     );
 
     let mut ctx = mina_signer::create(NetworkId::TESTNET);
-    let sig = ctx.sign(kp, tx);
+    let sig = ctx.sign(&kp, tx).expect("failed to sign");
 
     assert_eq!(ctx.verify(sig, kp.public, tx), true);
 
@@ -119,6 +135,12 @@ fn signer_zero_test() {
     assert_eq!(ctx.verify(sig3, kp.public, tx), false);
     sig3.rx = BaseField::zero();
     assert_eq!(ctx.verify(sig3, kp.public, tx), false);
+
+    // Invalid public keys
+    assert!(!ctx.verify(sig, PubKey::new(CurvePoint::zero()), tx));
+    let mut pt = kp.public.to_point();
+    pt.y += BaseField::from(1u64);
+    assert!(!ctx.verify(sig, PubKey::new(pt), tx));
 }
 
 #[test]
@@ -267,5 +289,372 @@ fn custom_signer_test() {
         NetworkId::MAINNET,
     );
     let tx = Transaction::new_payment(kp.public, kp.public, 2049, 1, 0);
-    ctx.sign(kp, tx);
+    ctx.sign(&kp, tx).expect("failed to sign");
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn network_id_serde() {
+    assert_eq!(
+        serde_json::to_string(&NetworkId::MAINNET).expect("failed to serialize"),
+        r#""mainnet""#
+    );
+    assert_eq!(
+        serde_json::to_string(&NetworkId::TESTNET).expect("failed to serialize"),
+        r#""testnet""#
+    );
+    assert_eq!(
+        serde_json::from_str::<NetworkId>(r#""mainnet""#).expect("failed to deserialize"),
+        NetworkId::MAINNET
+    );
+    assert_eq!(
+        serde_json::from_str::<NetworkId>(r#""devnet""#).expect("failed to deserialize"),
+        NetworkId::TESTNET
+    );
+    assert_eq!(
+        serde_json::to_string(&NetworkId::custom("berkeley").expect("invalid network"))
+            .expect("failed to serialize"),
+        r#""berkeley""#
+    );
+    assert!(serde_json::from_str::<NetworkId>(r#""""#).is_err());
+}
+
+#[test]
+fn network_id_test() {
+    assert_eq!("mainnet".parse(), Ok(NetworkId::MAINNET));
+    assert_eq!("testnet".parse(), Ok(NetworkId::TESTNET));
+    assert_eq!("devnet".parse(), Ok(NetworkId::TESTNET));
+    assert_eq!(
+        "berkeley".parse(),
+        Ok(NetworkId::custom("berkeley").expect("invalid network"))
+    );
+    assert_eq!("".parse::<NetworkId>(), Err(Error::NetworkId));
+    assert_eq!("r\u{e9}seau".parse::<NetworkId>(), Err(Error::NetworkId));
+
+    assert_eq!(NetworkId::MAINNET.to_string(), "mainnet");
+    assert_eq!(NetworkId::TESTNET.to_string(), "testnet");
+    assert_eq!(
+        NetworkId::custom("berkeley")
+            .expect("invalid network")
+            .to_string(),
+        "berkeley"
+    );
+
+    assert_eq!(NetworkId::MAINNET.to_bytes(), vec![0x01]);
+    assert_eq!(NetworkId::TESTNET.to_bytes(), vec![0x00]);
+    assert_eq!(NetworkId::custom("devnet"), Ok(NetworkId::TESTNET));
+    assert_eq!(NetworkId::custom("mainnet"), Ok(NetworkId::MAINNET));
+    assert_eq!(NetworkId::custom(""), Err(Error::NetworkId));
+    assert_eq!(NetworkId::custom("r\u{e9}seau"), Err(Error::NetworkId));
+    assert_eq!(
+        NetworkId::custom("berkeley")
+            .expect("invalid network")
+            .to_bytes(),
+        b"berkeley".to_vec()
+    );
+
+    // Custom network ids and signature domains as derived by o1js for custom networks: the
+    // network id is the little-endian integer of the name bytes (see `networkIdOfString`) and
+    // the domain is "<name>Signature" padded with '*' or truncated to 20 characters (see
+    // `createCustomPrefix`)
+    let network_id = NetworkId::custom("zeko-testnet").expect("invalid network");
+    assert_eq!(
+        BigUint::from_bytes_le(&network_id.to_bytes()),
+        BigUint::parse_bytes(b"74656e747365742d6f6b657a", 16).expect("invalid hex")
+    );
+    assert_eq!(network_id.signature_domain(), "zeko-testnetSignatur");
+    let network_id = NetworkId::custom("zeko").expect("invalid network");
+    assert_eq!(network_id.signature_domain(), "zekoSignature");
+    assert_eq!(
+        hash::prefix_to_field(&network_id.signature_domain()),
+        hash::prefix_to_field("zekoSignature*******")
+    );
+
+    // Signatures on custom networks are only valid on that network
+    let kp = Keypair::rand(&mut rand::rngs::OsRng);
+    let tx = Transaction::new_payment(kp.public, kp.public, 1729000000000, 2000000000, 1);
+    let mut ctx = mina_signer::create(NetworkId::custom("berkeley").expect("invalid network"));
+    let sig = ctx.sign(&kp, tx).expect("failed to sign");
+    assert!(ctx.verify(sig, kp.public, tx));
+    assert!(
+        !mina_signer::create(NetworkId::custom("devnet2").expect("invalid network"))
+            .verify(sig, kp.public, tx)
+    );
+    assert!(!mina_signer::create(NetworkId::TESTNET).verify(sig, kp.public, tx));
+    assert!(!mina_signer::create(NetworkId::MAINNET).verify(sig, kp.public, tx));
+
+    // Signatures on standard networks are detected as such
+    let sig = mina_signer::create(NetworkId::MAINNET)
+        .sign(&kp, tx)
+        .expect("failed to sign");
+    assert_eq!(
+        ctx.verify_detailed(sig, kp.public, tx),
+        Err(VerifyError::WrongNetwork(NetworkId::MAINNET))
+    );
+}
+
+#[test]
+#[cfg(feature = "parallel")]
+fn sign_many_test() {
+    let kp = Keypair::rand(&mut rand::rngs::OsRng);
+    let receiver = PubKey::from_address("B62qicipYxyEHu7QjUqS7QvBipTs5CzgkYZZZkPoKVYBu6tnDUcE9Zt")
+        .expect("invalid address");
+    let txs: Vec<Transaction> = (0..64)
+        .map(|nonce| {
+            Transaction::new_payment(kp.public, receiver, 1729000000000, 2000000000, nonce)
+        })
+        .collect();
+
+    let mut ctx = mina_signer::create(NetworkId::TESTNET);
+    let sigs = ctx.sign_many(&kp, &txs).expect("failed to sign");
+    assert_eq!(sigs.len(), txs.len());
+    for (sig, tx) in sigs.iter().zip(txs.iter()) {
+        assert_eq!(*sig, ctx.sign(&kp, *tx).expect("failed to sign"));
+        assert!(ctx.verify(*sig, kp.public, *tx));
+    }
+
+    assert_eq!(ctx.sign_many::<Transaction>(&kp, &[]), Ok(vec![]));
+}
+
+// Field elements signed with the network's signature domain (like o1js `Signature.create`)
+#[derive(Clone, Copy)]
+struct Fields([BaseField; 3]);
+
+impl Hashable for Fields {
+    fn to_roinput(self) -> ROInput {
+        let mut roi = ROInput::new();
+        for field in self.0.iter() {
+            roi.append_field(*field);
+        }
+
+        roi
+    }
+}
+
+impl Signable for Fields {
+    fn domain_string(network_id: &NetworkId) -> Cow<'static, str> {
+        network_id.signature_domain()
+    }
+}
+
+// Known-answer vectors produced by o1js (see tests/vectors/README.md)
+#[cfg(feature = "serde")]
+fn o1js_vectors() -> serde_json::Value {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/vectors/o1js.json");
+    let json = std::fs::read_to_string(path).expect("failed to read vectors");
+
+    serde_json::from_str(&json).expect("invalid vectors")
+}
+
+#[cfg(feature = "serde")]
+fn o1js_fields(vector: &serde_json::Value) -> Vec<BaseField> {
+    use mina_signer::FieldHelpers;
+
+    vector
+        .as_array()
+        .expect("invalid fields")
+        .iter()
+        .map(|x| {
+            BaseField::from_decimal(x.as_str().expect("invalid field")).expect("invalid field")
+        })
+        .collect()
+}
+
+#[test]
+#[cfg(feature = "serde")]
+#[ignore = "requires tests/vectors/o1js.json generated with o1js"]
+fn kimchi_o1js_vectors_test() {
+    let vectors = o1js_vectors();
+    let signatures = vectors["signatures"].as_array().expect("invalid vectors");
+    assert!(!signatures.is_empty());
+
+    for vector in signatures {
+        let kp = Keypair::from_base58(vector["privateKey"].as_str().expect("invalid key"))
+            .expect("failed to create keypair");
+        let network_id: NetworkId = vector["network"]
+            .as_str()
+            .expect("invalid network")
+            .parse()
+            .expect("invalid network");
+        let fields = o1js_fields(&vector["fields"]);
+        let msg = Fields([fields[0], fields[1], fields[2]]);
+        let expected = vector["signature"].as_str().expect("invalid signature");
+
+        let mut ctx = mina_signer::create_kimchi(network_id);
+        let sig = ctx.sign(&kp, msg).expect("failed to sign");
+        assert_eq!(sig.to_base58(), expected);
+        assert!(ctx.verify(
+            Signature::from_base58(expected).expect("invalid signature"),
+            kp.public,
+            msg
+        ));
+    }
+}
+
+#[test]
+#[cfg(feature = "serde")]
+#[ignore = "requires tests/vectors/o1js.json generated with o1js"]
+fn hash_o1js_vectors_test() {
+    use mina_signer::FieldHelpers;
+
+    let vectors = o1js_vectors();
+    let output = |vector: &serde_json::Value| {
+        BaseField::from_decimal(vector["output"].as_str().expect("invalid output"))
+            .expect("invalid output")
+    };
+
+    for (name, kimchi) in [("hashWithPrefix", true), ("hashWithPrefixLegacy", false)] {
+        let hashes = vectors[name].as_array().expect("invalid vectors");
+        assert!(!hashes.is_empty());
+
+        for vector in hashes {
+            let prefix = vector["prefix"].as_str().expect("invalid prefix");
+            let input = o1js_fields(&vector["input"]);
+            let hash = if kimchi {
+                hash::create_kimchi().hash_with_prefix(prefix, &input)
+            } else {
+                hash::create_legacy().hash_with_prefix(prefix, &input)
+            };
+            assert_eq!(hash, Ok(output(vector)), "{} {}", name, prefix);
+        }
+    }
+
+    let hashes = vectors["hashPacked"].as_array().expect("invalid vectors");
+    assert!(!hashes.is_empty());
+    for vector in hashes {
+        let number = |key: &str| {
+            vector[key]
+                .as_str()
+                .expect("invalid number")
+                .parse::<u64>()
+                .expect("invalid number")
+        };
+
+        let mut roi = ROInput::new();
+        roi.append_field(
+            BaseField::from_decimal(vector["field"].as_str().expect("invalid field"))
+                .expect("invalid field"),
+        );
+        roi.append_u32(number("u32") as u32);
+        roi.append_u64(number("u64"));
+        roi.append_bit(vector["bit"].as_bool().expect("invalid bit"));
+        assert_eq!(hash::create_kimchi().hash(&roi), output(vector));
+    }
+}
+
+#[test]
+fn kimchi_signer_test() {
+    let kp = Keypair::rand(&mut rand::rngs::OsRng);
+    let msg = Fields([
+        BaseField::from(1u64),
+        BaseField::from(1729u64),
+        -BaseField::from(1u64),
+    ]);
+
+    let mut testnet_ctx = mina_signer::create_kimchi(NetworkId::TESTNET);
+    let mut mainnet_ctx = mina_signer::create_kimchi(NetworkId::MAINNET);
+    let testnet_sig = testnet_ctx.sign(&kp, msg).expect("failed to sign");
+    let mainnet_sig = mainnet_ctx.sign(&kp, msg).expect("failed to sign");
+
+    assert!(testnet_ctx.verify(testnet_sig, kp.public, msg));
+    assert!(mainnet_ctx.verify(mainnet_sig, kp.public, msg));
+    assert_eq!(
+        testnet_ctx.verify_detailed(mainnet_sig, kp.public, msg),
+        Err(VerifyError::WrongNetwork(NetworkId::MAINNET))
+    );
+    assert_eq!(
+        testnet_ctx.verify_batch(&[(testnet_sig, kp.public, msg), (mainnet_sig, kp.public, msg)]),
+        vec![true, false]
+    );
+
+    // Kimchi and legacy signatures are not interchangeable
+    let mut legacy_ctx = mina_signer::create(NetworkId::TESTNET);
+    assert!(!legacy_ctx.verify(testnet_sig, kp.public, msg));
+    let legacy_sig = legacy_ctx.sign(&kp, msg).expect("failed to sign");
+    assert_ne!(legacy_sig, testnet_sig);
+    assert!(!testnet_ctx.verify(legacy_sig, kp.public, msg));
+}
+
+#[test]
+fn verify_batch_test() {
+    let mut ctx = mina_signer::create(NetworkId::TESTNET);
+    let receiver = PubKey::from_address("B62qicipYxyEHu7QjUqS7QvBipTs5CzgkYZZZkPoKVYBu6tnDUcE9Zt")
+        .expect("invalid address");
+
+    let mut batch = vec![];
+    for nonce in 0..16 {
+        let kp = Keypair::rand(&mut rand::rngs::OsRng);
+        let tx = Transaction::new_payment(kp.public, receiver, 1729000000000, 2000000000, nonce);
+        batch.push((ctx.sign(&kp, tx).expect("failed to sign"), kp.public, tx));
+    }
+
+    assert_eq!(ctx.verify_batch(&batch), vec![true; batch.len()]);
+    assert!(ctx.verify_batch::<Transaction>(&[]).is_empty());
+
+    // Corrupt some items in different ways
+    batch[1].0.s += ScalarField::from(1u64);
+    batch[6].0.rx = BaseField::zero();
+    batch[7].0 = batch[8].0;
+    batch[10].1 = receiver;
+    batch[12].2.nonce += 1;
+    batch[15].1 = PubKey::new(CurvePoint::zero());
+
+    let results = ctx.verify_batch(&batch);
+    for (i, (sig, public, tx)) in batch.iter().enumerate() {
+        assert_eq!(results[i], ctx.verify(*sig, *public, *tx));
+        assert_eq!(results[i], ![1, 6, 7, 10, 12, 15].contains(&i));
+    }
+
+    // Signatures on the other network are rejected
+    let mut mainnet_ctx = mina_signer::create(NetworkId::MAINNET);
+    assert_eq!(mainnet_ctx.verify_batch(&batch[2..4]), vec![false, false]);
+}
+
+// Recompute the challenge of signature `sig` with `hasher` and check that s*G - e*P = R
+fn check_challenge<SC: SpongeConstants>(
+    hasher: &mut Hasher<SC>,
+    sig: Signature,
+    public: PubKey,
+    tx: Transaction,
+) -> bool {
+    let mut roi = tx.to_roinput();
+    roi.append_field(public.to_point().x);
+    roi.append_field(public.to_point().y);
+    roi.append_field(sig.rx);
+
+    let e = hasher
+        .hash_with_prefix(
+            &Transaction::domain_string(&NetworkId::TESTNET),
+            &hasher.to_fields(&roi),
+        )
+        .expect("failed to hash");
+    let e = ScalarField::from_repr(e.into_repr()).expect("invalid challenge");
+    let r = CurvePoint::prime_subgroup_generator().mul(sig.s) - public.to_point().mul(e);
+
+    r.into_affine().x == sig.rx
+}
+
+#[test]
+fn hash_test() {
+    let kp = Keypair::rand(&mut rand::rngs::OsRng);
+    let receiver = PubKey::from_address("B62qicipYxyEHu7QjUqS7QvBipTs5CzgkYZZZkPoKVYBu6tnDUcE9Zt")
+        .expect("invalid address");
+    let tx = Transaction::new_payment(kp.public, receiver, 1729000000000, 2000000000, 16)
+        .set_valid_until(271828)
+        .set_memo_str("Hello Mina!");
+
+    // The hash module computes the same challenges as the signers
+    let legacy_sig = mina_signer::create(NetworkId::TESTNET)
+        .sign(&kp, tx)
+        .expect("failed to sign");
+    let kimchi_sig = mina_signer::create_kimchi(NetworkId::TESTNET)
+        .sign(&kp, tx)
+        .expect("failed to sign");
+
+    let mut legacy = hash::create_legacy();
+    let mut kimchi = hash::create_kimchi();
+    assert!(check_challenge(&mut legacy, legacy_sig, kp.public, tx));
+    assert!(check_challenge(&mut kimchi, kimchi_sig, kp.public, tx));
+    assert!(!check_challenge(&mut legacy, kimchi_sig, kp.public, tx));
+    assert!(!check_challenge(&mut kimchi, legacy_sig, kp.public, tx));
 }
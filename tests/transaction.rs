@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+
 use mina_signer::{CompressedPubKey, Hashable, NetworkId, PubKey, ROInput, Signable};
 
 const MEMO_BYTES: usize = 34;
@@ -53,12 +55,9 @@ impl Hashable for Transaction {
 }
 
 impl Signable for Transaction {
-    fn domain_string(network_id: NetworkId) -> &'static str {
-        // Domain strings must have length <= 20
-        match network_id {
-            NetworkId::MAINNET => "MinaSignatureMainnet",
-            NetworkId::TESTNET => "CodaSignature",
-        }
+    fn domain_string(network_id: &NetworkId) -> Cow<'static, str> {
+        // Mina transaction signature domain (e.g. "MinaSignatureMainnet" or "CodaSignature")
+        network_id.signature_domain()
     }
 }
 
@@ -128,13 +127,27 @@ use mina_signer::Keypair;
 #[test]
 fn transaction_domain() {
     assert_eq!(
-        Transaction::domain_string(NetworkId::MAINNET),
+        Transaction::domain_string(&NetworkId::MAINNET),
         "MinaSignatureMainnet"
     );
     assert_eq!(
-        Transaction::domain_string(NetworkId::TESTNET),
+        Transaction::domain_string(&NetworkId::TESTNET),
+        "CodaSignature"
+    );
+    assert_eq!(
+        Transaction::domain_string(&"devnet".parse().expect("invalid network")),
         "CodaSignature"
     );
+    assert_eq!(
+        Transaction::domain_string(&NetworkId::custom("berkeley").expect("invalid network")),
+        "berkeleySignature"
+    );
+    assert_eq!(
+        Transaction::domain_string(
+            &NetworkId::custom("mina-private-testnet").expect("invalid network")
+        ),
+        "mina-private-testnet"
+    );
 }
 
 #[test]
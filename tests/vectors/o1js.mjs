@@ -0,0 +1,83 @@
+// Generate the o1js known-answer vectors (tests/vectors/o1js.json)
+//
+//   npm install o1js mina-signer
+//   node tests/vectors/o1js.mjs > tests/vectors/o1js.json
+
+import { Bool, Field, Poseidon, PrivateKey, Signature, Struct, UInt32, UInt64 } from 'o1js';
+// N.B. The legacy Poseidon hash is only exposed by o1js' internal mina-signer module
+import { HashLegacy } from 'o1js/dist/node/mina-signer/src/poseidon-bigint.js';
+import Client from 'mina-signer';
+
+const privateKey = 'EKFKgDtU3rcuFTVSEpmpXSkukjmX4cKefYREi6Sdsk7E7wsT7KRw';
+const fields = [1n, 1729n, 42n];
+
+// Field message signatures, i.e. o1js `Signature.create` (testnet) and mina-signer
+// `signFields` on the other networks
+const signatures = [
+  {
+    network: 'testnet',
+    privateKey,
+    fields: fields.map(String),
+    signature: Signature.create(
+      PrivateKey.fromBase58(privateKey),
+      fields.map((x) => Field(x))
+    ).toBase58(),
+  },
+  ...['mainnet', 'zeko'].map((network) => ({
+    network,
+    privateKey,
+    fields: fields.map(String),
+    signature: new Client({ network }).signFields(fields, privateKey).signature,
+  })),
+];
+
+// Poseidon hashes with domain prefix (o1js does not pad prefixes, so they are given padded
+// with '*' to 20 characters like Mina's)
+const prefixInputs = [
+  ['MinaSignatureMainnet', [1n, 2n]],
+  ['CodaSignature*******', [1n, 2n]],
+  ['CodaSignature*******', [31n, 42n, 1729n, 0n]],
+  ['CodaSignature*******', []],
+];
+const hashWithPrefix = prefixInputs.map(([prefix, input]) => ({
+  prefix,
+  input: input.map(String),
+  output: Poseidon.hashWithPrefix(
+    prefix,
+    input.map((x) => Field(x))
+  ).toString(),
+}));
+const hashWithPrefixLegacy = prefixInputs.map(([prefix, input]) => ({
+  prefix,
+  input: input.map(String),
+  output: HashLegacy.hashWithPrefix(prefix, input).toString(),
+}));
+
+// Poseidon hash of a packed input (i.e. a field followed by a u32, u64 and bit)
+class Packed extends Struct({ field: Field, u32: UInt32, u64: UInt64, bit: Bool }) {}
+const hashPacked = [
+  [31n, 42n, 1729000000000n, true],
+  [0n, 0n, 0n, false],
+].map(([field, u32, u64, bit]) => ({
+  field: String(field),
+  u32: String(u32),
+  u64: String(u64),
+  bit,
+  output: Poseidon.hashPacked(
+    Packed,
+    new Packed({
+      field: Field(field),
+      u32: UInt32.from(u32),
+      u64: UInt64.from(u64),
+      bit: Bool(bit),
+    })
+  ).toString(),
+}));
+
+console.log(
+  JSON.stringify(
+    { signatures, hashWithPrefix, hashWithPrefixLegacy, hashPacked },
+    null,
+    2
+  )
+);